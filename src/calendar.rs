@@ -0,0 +1,96 @@
+//! The missions as an iCalendar archive, one all-day event per mission.
+
+use crate::{
+    model::ArticleNumber, redirects::percent_encode, render::RenderedArticle,
+    source::SourceArticle, target::OutputTarget,
+};
+use chrono::Days;
+
+/// Name of the calendar in the output directory.
+pub const CALENDAR_FILE: &str = "einsaetze.ics";
+
+/// Lines longer than this many bytes have to be folded.
+const MAX_LINE_LENGTH: usize = 75;
+
+/// The calendar of the articles of `previews`, as returned by
+/// [`Converter::preview`](crate::Converter::preview), linking to their pages below
+/// `base_url` if `target` knows their URL.
+pub fn ics(
+    previews: &[(SourceArticle, RenderedArticle)],
+    target: &dyn OutputTarget,
+    base_url: &str,
+) -> String {
+    let mut calendar = String::new();
+    push_line(&mut calendar, "BEGIN:VCALENDAR");
+    push_line(&mut calendar, "VERSION:2.0");
+    push_line(
+        &mut calendar,
+        "PRODID:-//ff-website-converter//Einsaetze//DE",
+    );
+    for (_, rendered) in previews {
+        let article = &rendered.article;
+        let date = article.date.date();
+        let uid = match article.original_id {
+            Some(id) => id.to_string(),
+            None => format!("{}-{}", rendered.year, ArticleNumber(rendered.index)),
+        };
+        push_line(&mut calendar, "BEGIN:VEVENT");
+        push_line(
+            &mut calendar,
+            &format!("UID:einsatz-{}@ff-website-converter", uid),
+        );
+        push_line(
+            &mut calendar,
+            &format!("DTSTAMP:{}", article.date.format("%Y%m%dT%H%M%SZ")),
+        );
+        push_line(
+            &mut calendar,
+            &format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")),
+        );
+        let end = date.checked_add_days(Days::new(1)).unwrap_or(date);
+        push_line(
+            &mut calendar,
+            &format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")),
+        );
+        push_line(
+            &mut calendar,
+            &format!("SUMMARY:{}", escape(&article.title)),
+        );
+        if let Some(path) = target.article_url(article, rendered.year, rendered.index) {
+            push_line(
+                &mut calendar,
+                &format!(
+                    "URL:{}{}",
+                    base_url.trim_end_matches('/'),
+                    percent_encode(&path)
+                ),
+            );
+        }
+        push_line(&mut calendar, "END:VEVENT");
+    }
+    push_line(&mut calendar, "END:VCALENDAR");
+    calendar
+}
+
+/// Escapes the characters with a meaning in text values.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Appends `line` with CRLF, folded into continuation lines starting with a space
+/// without splitting characters.
+fn push_line(calendar: &mut String, line: &str) {
+    let mut length = 0;
+    for character in line.chars() {
+        if length + character.len_utf8() > MAX_LINE_LENGTH {
+            calendar.push_str("\r\n ");
+            length = 1;
+        }
+        calendar.push(character);
+        length += character.len_utf8();
+    }
+    calendar.push_str("\r\n");
+}
@@ -0,0 +1,117 @@
+//! The articles a conversion has written completely, so that a run that crashed or was
+//! stopped can be resumed instead of starting over, see
+//! [`Converter::resume`](crate::Converter::resume).
+
+use crate::{error::ConverterError, model::Article, output::CHECKPOINT_FILE};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// A written article, one JSON object per line of the [`CHECKPOINT_FILE`]. A line cut
+/// off by a crash is ignored, its article is written again.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct Entry {
+    /// Path of the article file in the content directory.
+    path: String,
+    /// The [content hash](crate::model::Article::content_hash) of the article, an article
+    /// changed since is written again.
+    hash: String,
+}
+
+pub(crate) struct Checkpoint {
+    path: PathBuf,
+    resume: bool,
+    /// What the resumed run wrote.
+    done: HashSet<Entry>,
+    /// Opened with the first completed article, so that a run that fails before leaves
+    /// the checkpoint of the previous one as it was.
+    file: Mutex<Option<File>>,
+}
+
+impl Checkpoint {
+    /// Starts the checkpoint of a run in `output_dir`, continuing the one of the previous
+    /// run with `resume` and starting over without, once the first article is complete.
+    pub fn open(output_dir: &Path, resume: bool) -> Result<Self, ConverterError> {
+        let path = output_dir.join(CHECKPOINT_FILE);
+        let done = if resume {
+            match fs::read_to_string(&path) {
+                Ok(text) => text
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect(),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => HashSet::new(),
+                Err(source) => return Err(ConverterError::ReadFailed { path, source }),
+            }
+        } else {
+            HashSet::new()
+        };
+        Ok(Checkpoint {
+            path,
+            resume,
+            done,
+            file: Mutex::default(),
+        })
+    }
+
+    /// Whether the resumed run wrote the article file at `path` with the content `hash`.
+    pub fn is_done(&self, path: &Path, hash: &str) -> bool {
+        self.done.contains(&Entry {
+            path: path.to_string_lossy().into_owned(),
+            hash: hash.to_string(),
+        })
+    }
+
+    /// Records that `article`, written to the file at `path` with the content `hash`,
+    /// is complete with its images and thumbnail.
+    pub fn complete(
+        &self,
+        article: &Article,
+        path: &Path,
+        hash: &str,
+    ) -> Result<(), ConverterError> {
+        let entry = Entry {
+            path: path.to_string_lossy().into_owned(),
+            hash: hash.to_string(),
+        };
+        let mut line = serde_json::to_string(&entry).expect("Entries always serialize");
+        line.push('\n');
+        let mut file = self.file.lock().unwrap();
+        // One write per line, so that a crash cuts off at most the last one.
+        let written = match &mut *file {
+            Some(file) => file.write_all(line.as_bytes()),
+            None => self
+                .create()
+                .and_then(|created| file.insert(created).write_all(line.as_bytes())),
+        };
+        written.map_err(|source| ConverterError::WriteFailed {
+            article: Some(article.into()),
+            path: self.path.clone(),
+            source,
+        })
+    }
+
+    /// Creates the checkpoint file, or appends to the one of the previous run when resuming.
+    fn create(&self) -> io::Result<File> {
+        fs::create_dir_all(self.path.parent().expect("Checkpoint without directory"))?;
+        OpenOptions::new()
+            .create(true)
+            .append(self.resume)
+            .write(true)
+            .truncate(!self.resume)
+            .open(&self.path)
+    }
+
+    /// Removes the checkpoint once the conversion is complete, there is nothing to resume.
+    pub fn remove(self) -> io::Result<()> {
+        drop(self.file);
+        match fs::remove_file(&self.path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Pushing the articles into a headless CMS over its REST API instead of writing
+//! files, see [`HeadlessCms::push`].
+
+use crate::{
+    download, model::ArticleNumber, render::RenderedArticle, source::SourceArticle,
+    target::TargetOptions,
+};
+use reqwest::{multipart, Client, RequestBuilder};
+use serde_json::{json, Value};
+use std::{fs, io, path::Path, time::Duration};
+use tokio::time::{self, Interval, MissedTickBehavior};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmsKind {
+    /// Strapi 4, the collection is the plural API ID of the content type.
+    Strapi,
+    /// Directus 10, the collection is its name.
+    Directus,
+}
+
+/// A headless CMS and the collection the articles are created in.
+///
+/// The collection needs the fields `title`, `date`, `slug`, `text`, `original_id`,
+/// `year`, `number` and `images`, a relation to multiple files.
+#[derive(Debug, Clone)]
+pub struct HeadlessCms {
+    pub kind: CmsKind,
+    /// Base URL of the CMS, e.g. `https://cms.example.org`.
+    pub url: String,
+    /// API token, sent as bearer token.
+    pub token: String,
+    pub collection: String,
+    /// Limits the requests per second, CMSs tend to reject bursts.
+    pub requests_per_second: Option<f64>,
+}
+
+impl HeadlessCms {
+    /// Uploads the images and creates an entry for every article of `previews`, as
+    /// returned by [`Converter::preview`](crate::Converter::preview), one after another.
+    /// The dates are in the [`timezone`](TargetOptions::timezone) of `options`. Every
+    /// push creates new entries, the ones of earlier pushes are left alone. Returns the
+    /// number of created entries.
+    pub fn push(
+        &self,
+        previews: &[(SourceArticle, RenderedArticle)],
+        old_website_dir: &Path,
+        options: &TargetOptions,
+    ) -> io::Result<usize> {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?
+            .block_on(async {
+                let mut session = Session {
+                    cms: self,
+                    client: Client::new(),
+                    interval: self.requests_per_second.map(|requests_per_second| {
+                        let mut interval =
+                            time::interval(Duration::from_secs_f64(1.0 / requests_per_second));
+                        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                        interval
+                    }),
+                };
+                for (_, rendered) in previews {
+                    session.create(rendered, old_website_dir, options).await?;
+                    tracing::info!(
+                        "Article {}-{} pushed",
+                        rendered.year,
+                        ArticleNumber(rendered.index)
+                    );
+                }
+                Ok(previews.len())
+            })
+    }
+}
+
+struct Session<'a> {
+    cms: &'a HeadlessCms,
+    client: Client,
+    interval: Option<Interval>,
+}
+
+impl Session<'_> {
+    async fn create(
+        &mut self,
+        rendered: &RenderedArticle,
+        old_website_dir: &Path,
+        options: &TargetOptions,
+    ) -> io::Result<()> {
+        let mut images = Vec::new();
+        for (source, destination) in &rendered.images {
+            let image = if download::is_remote(source) {
+                let url = source.to_string_lossy();
+                match self.download(&url).await {
+                    Ok(image) => image,
+                    Err(error) => {
+                        tracing::warn!("Image {} left out, failed to download: {}", url, error);
+                        continue;
+                    }
+                }
+            } else {
+                let path = old_website_dir.join(source);
+                fs::read(&path).map_err(|error| {
+                    io::Error::new(error.kind(), format!("{}: {}", path.display(), error))
+                })?
+            };
+            let file_name = destination
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            images.push(self.upload(image, file_name).await?);
+        }
+
+        let article = &rendered.article;
+        // Without a timezone, the date is local to the old website like in the files.
+        let date = match options.zoned_date(article.date) {
+            Some(date) => date.to_rfc3339(),
+            None => article.date.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        };
+        let fields = json!({
+            "title": article.title,
+            "date": date,
+            "slug": article.slug,
+            "text": article.text,
+            "original_id": article.original_id,
+            "year": rendered.year,
+            "number": ArticleNumber(rendered.index).to_string(),
+            "images": images,
+        });
+        let (path, body) = match self.cms.kind {
+            CmsKind::Strapi => (
+                format!("api/{}", self.cms.collection),
+                json!({ "data": fields }),
+            ),
+            CmsKind::Directus => (format!("items/{}", self.cms.collection), fields),
+        };
+        let request = self.request(&path).json(&body);
+        self.send(request).await?;
+        Ok(())
+    }
+
+    /// Uploads an image and returns its ID.
+    async fn upload(&mut self, image: Vec<u8>, file_name: String) -> io::Result<Value> {
+        let (path, field) = match self.cms.kind {
+            CmsKind::Strapi => ("api/upload", "files"),
+            CmsKind::Directus => ("files", "file"),
+        };
+        let form =
+            multipart::Form::new().part(field, multipart::Part::bytes(image).file_name(file_name));
+        let response = self.send(self.request(path).multipart(form)).await?;
+        let id = match self.cms.kind {
+            CmsKind::Strapi => &response[0]["id"],
+            CmsKind::Directus => &response["data"]["id"],
+        };
+        if id.is_null() {
+            return Err(io::Error::other(format!(
+                "The upload returned no ID: {}",
+                response
+            )));
+        }
+        Ok(id.clone())
+    }
+
+    /// Downloads an image the article links with a URL, from the old website and not
+    /// within the rate limit of the CMS.
+    async fn download(&self, url: &str) -> reqwest::Result<Vec<u8>> {
+        let response = self.client.get(url).send().await?.error_for_status()?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    fn request(&self, path: &str) -> RequestBuilder {
+        let url = format!("{}/{}", self.cms.url.trim_end_matches('/'), path);
+        self.client.post(url).bearer_auth(&self.cms.token)
+    }
+
+    async fn send(&mut self, request: RequestBuilder) -> io::Result<Value> {
+        if let Some(interval) = &mut self.interval {
+            interval.tick().await;
+        }
+        let response = request.send().await.map_err(io::Error::other)?;
+        let status = response.status();
+        let body = response.text().await.map_err(io::Error::other)?;
+        if !status.is_success() {
+            return Err(io::Error::other(format!(
+                "The CMS answered {}: {}",
+                status, body
+            )));
+        }
+        Ok(serde_json::from_str(&body).unwrap_or(Value::Null))
+    }
+}
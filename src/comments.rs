@@ -0,0 +1,128 @@
+//! Reader comments of the old website, from the export of the JComments component,
+//! as Hugo data files next to the content.
+
+use crate::{
+    error::ConverterError,
+    render::RenderedArticle,
+    source::{string_or_number, SourceArticle, DATE_TIME_FORMAT},
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, path::Path};
+
+/// Where the comments go in the output directory, one file per article as
+/// `[<section>/]<year>/<number>.json`, read by the templates as `site.Data.comments`.
+pub const COMMENTS_DIR: &str = "data/comments";
+
+/// A published comment. The email addresses of the authors are left out.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Comment {
+    /// ID of the article in the source CMS.
+    #[serde(skip)]
+    pub article_id: u64,
+    pub author: String,
+    pub date: NaiveDateTime,
+    pub text: String,
+}
+
+/// One row of the phpMyAdmin export of the `#__jcomments` table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct CommentRow {
+    #[serde(deserialize_with = "string_or_number")]
+    object_id: Option<String>,
+    object_group: Option<String>,
+    name: Option<String>,
+    date: Option<String>,
+    comment: Option<String>,
+    #[serde(deserialize_with = "string_or_number")]
+    published: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Export {
+    data: Vec<CommentRow>,
+}
+
+/// Reads the published comments on articles from the export at `path`.
+pub fn load(path: &Path) -> Result<Vec<Comment>, ConverterError> {
+    let input_parse = |message: String| ConverterError::InputParse {
+        path: path.to_path_buf(),
+        message,
+    };
+    let json = fs::read(path).map_err(|source| ConverterError::ReadFailed {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let export: Export =
+        serde_json::from_slice(&json).map_err(|error| input_parse(error.to_string()))?;
+
+    let mut comments = Vec::new();
+    for (entry, row) in export.data.into_iter().enumerate() {
+        let on_article = row.object_group.as_deref() == Some("com_content");
+        if !on_article || row.published.as_deref() != Some("1") {
+            continue;
+        }
+        let article_id = row
+            .object_id
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(|| input_parse(format!("comment {}: no article ID", entry)))?;
+        let date = row
+            .date
+            .and_then(|date| NaiveDateTime::parse_from_str(&date, DATE_TIME_FORMAT).ok())
+            .ok_or_else(|| input_parse(format!("comment {}: no valid date", entry)))?;
+        comments.push(Comment {
+            article_id,
+            author: row.name.unwrap_or_default(),
+            date,
+            text: row.comment.unwrap_or_default().trim().to_string(),
+        });
+    }
+    Ok(comments)
+}
+
+/// Writes the comments on the articles of `previews`, as returned by
+/// [`Converter::preview`](crate::Converter::preview), in the order they were written.
+/// Comments on other articles are left out, returns the number of articles with comments.
+pub fn write(
+    mut comments: Vec<Comment>,
+    previews: &[(SourceArticle, RenderedArticle)],
+    output_dir: &Path,
+) -> Result<usize, ConverterError> {
+    let articles: HashMap<u64, &RenderedArticle> = previews
+        .iter()
+        .filter_map(|(_, rendered)| Some((rendered.article.original_id?, rendered)))
+        .collect();
+    comments.sort_by_key(|comment| comment.date);
+    let mut by_article: HashMap<u64, Vec<Comment>> = HashMap::new();
+    for comment in comments {
+        if articles.contains_key(&comment.article_id) {
+            by_article
+                .entry(comment.article_id)
+                .or_default()
+                .push(comment);
+        }
+    }
+
+    for (id, comments) in &by_article {
+        let rendered = articles[id];
+        let mut path = output_dir.join(COMMENTS_DIR);
+        if let Some(dir) = &rendered.article.section {
+            path.push(dir);
+        }
+        let path = path.join(rendered.year.to_string()).join(format!(
+            "{}.json",
+            rendered.article.dir_name(rendered.index)
+        ));
+        let write_failed = |source| ConverterError::WriteFailed {
+            article: Some((&rendered.article).into()),
+            path: path.clone(),
+            source,
+        };
+        let json = serde_json::to_vec_pretty(comments).expect("Comments serialize to JSON");
+        fs::create_dir_all(path.parent().expect("The path has a year directory"))
+            .map_err(write_failed)?;
+        fs::write(&path, json).map_err(write_failed)?;
+    }
+    Ok(by_article.len())
+}
@@ -0,0 +1,362 @@
+//! The settings of a conversion as a TOML file, see [`Config`].
+
+use crate::{
+    builder::ConfigError,
+    images::{ImageEncoding, ThumbnailSize},
+    model::Section,
+    output::{Existing, MissingImages},
+    target::{Field, Strings, TargetOptions},
+    ConverterBuilder,
+};
+use chrono::NaiveDate;
+use serde::{de, de::DeserializeOwned, Deserialize, Deserializer};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Settings of a conversion, e.g. from a `converter.toml` like
+///
+/// ```toml
+/// input = "export/missions.json"
+/// years = [2018, 2019]
+/// catid = 5
+///
+/// [target_options]
+/// date_format = "%d.%m.%Y"
+///
+/// [target_options.frontmatter]
+/// thumbnail = "image"
+/// ```
+///
+/// Unset settings keep the defaults of the [`ConverterBuilder`].
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub source: Option<String>,
+    /// One input or a list of them, like `input = ["missions_2018.json", "missions_2019.json"]`,
+    /// see [`ConverterBuilder::inputs`].
+    #[serde(deserialize_with = "one_or_many")]
+    pub input: Option<Vec<PathBuf>>,
+    /// Reads the Joomla database instead, see [`ConverterBuilder::db_url`].
+    pub db_url: Option<String>,
+    pub db_prefix: Option<String>,
+    pub target: Option<String>,
+    pub old_website_dir: Option<PathBuf>,
+    pub output_dir: Option<PathBuf>,
+    pub cache_dir: Option<PathBuf>,
+    /// `cache = false` processes every article and image again, see [`ConverterBuilder::cache`].
+    pub cache: Option<bool>,
+    pub years: Option<Vec<u32>>,
+    /// Only the articles created on this day or later, like `from = 2024-03-01`, see
+    /// [`ConverterBuilder::from`].
+    #[serde(deserialize_with = "date")]
+    pub from: Option<NaiveDate>,
+    /// Only the articles created on this day or earlier, see [`ConverterBuilder::until`].
+    #[serde(deserialize_with = "date")]
+    pub until: Option<NaiveDate>,
+    pub catid: Option<u32>,
+    /// Several categories with their sections instead of `catid`, see
+    /// [`ConverterBuilder::sections`].
+    pub sections: Option<Vec<Section>>,
+    /// Regex replacements after the built-in text cleanup, see [`ConverterBuilder::transform`].
+    pub transforms: Vec<TransformConfig>,
+    /// Templates of the tags of Joomla plugins by the name of the plugin, like
+    /// `plugins = { loadposition = "" }`, see [`ConverterBuilder::plugin`].
+    pub plugins: BTreeMap<String, String>,
+    /// Puts every sentence on its own line, see [`ConverterBuilder::sentence_per_line`].
+    pub sentence_per_line: Option<bool>,
+    /// The line between the intro and the full text, empty to join them.
+    pub read_more: Option<String>,
+    /// Re-encodes the images, see [`ConverterBuilder::images`].
+    pub images: Option<ImageEncoding>,
+    /// Scales the thumbnails, see [`ConverterBuilder::thumbnails`].
+    pub thumbnails: Option<ThumbnailSize>,
+    /// Writes the thumbnails into the article directories, see
+    /// [`ConverterBuilder::bundle_thumbnails`].
+    pub bundle_thumbnails: Option<bool>,
+    /// Removes the metadata of the copied images, see [`ConverterBuilder::strip_exif`].
+    pub strip_exif: Option<bool>,
+    /// Pattern of the image names, see [`ConverterBuilder::image_names`].
+    pub image_names: Option<String>,
+    /// Names the articles after their slugs, see [`ConverterBuilder::slug_dirs`].
+    pub slug_dirs: Option<bool>,
+    /// Names the articles after their IDs, see [`ConverterBuilder::id_dirs`].
+    pub id_dirs: Option<bool>,
+    /// Converts the unpublished articles as drafts, see [`ConverterBuilder::drafts`].
+    pub drafts: Option<bool>,
+    /// Names the articles after their languages, see [`ConverterBuilder::multilingual`].
+    pub multilingual: Option<bool>,
+    /// Writes the hash of the source of every article into its frontmatter, see
+    /// [`ConverterBuilder::converter_hash`].
+    pub converter_hash: Option<bool>,
+    /// Continues the conversion that crashed or was stopped, see
+    /// [`ConverterBuilder::resume`].
+    pub resume: Option<bool>,
+    /// Writes the years into a staging directory first, see [`ConverterBuilder::staging`].
+    pub staging: Option<bool>,
+    /// What to do with missing images, see [`MissingImages`].
+    pub missing_images: Option<MissingImages>,
+    /// Limit of the combined throughput of reading and downloading images in MiB/s.
+    pub io_limit: Option<f64>,
+    /// What to do with the output of an earlier run, see [`Existing`].
+    pub existing: Option<Existing>,
+    /// File with the names of the authors, see [`ConverterBuilder::authors`].
+    pub authors: Option<PathBuf>,
+    /// File with extra frontmatter fields, see [`ConverterBuilder::fields`].
+    pub fields: Option<PathBuf>,
+    /// File with the texts written besides the articles, see [`ConverterBuilder::strings`].
+    pub strings: Option<PathBuf>,
+    /// Names of the categories by their ID, like `categories = { 5 = "Einsätze" }`.
+    #[serde(deserialize_with = "by_id")]
+    pub categories: HashMap<u32, String>,
+    /// Scheme of the article URLs of the old website, see [`ConverterBuilder::old_url`].
+    pub old_url: Option<String>,
+    /// Rewrites the links between the articles, see [`ConverterBuilder::rewrite_links`].
+    pub rewrite_links: Option<bool>,
+    /// Copies the documents the articles link to, see [`ConverterBuilder::attachments`].
+    pub attachments: Option<bool>,
+    pub target_options: TargetOptions,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransformConfig {
+    pub regex: String,
+    pub replacement: String,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = fs::read_to_string(path).map_err(|source| ConfigError::ReadConfig {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        toml::from_str(&text).map_err(|error| ConfigError::ParseConfig {
+            path: path.to_path_buf(),
+            message: error.to_string(),
+        })
+    }
+
+    /// The files the settings name besides the input.
+    pub fn files(&self) -> Vec<PathBuf> {
+        [&self.authors, &self.fields, &self.strings]
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    /// Applies the settings that are set to `builder`.
+    pub fn apply(&self, mut builder: ConverterBuilder) -> ConverterBuilder {
+        if let Some(db_url) = &self.db_url {
+            builder = builder.db_url(db_url);
+        }
+        if let Some(db_prefix) = &self.db_prefix {
+            builder = builder.db_prefix(db_prefix);
+        }
+        if let Some(source) = &self.source {
+            builder = builder.source(source);
+        }
+        if let Some(inputs) = &self.input {
+            builder = builder.inputs(inputs);
+        }
+        if let Some(target) = &self.target {
+            builder = builder.target_name(target);
+        }
+        if let Some(old_website_dir) = &self.old_website_dir {
+            builder = builder.old_website_dir(old_website_dir);
+        }
+        if let Some(output_dir) = &self.output_dir {
+            builder = builder.output_dir(output_dir);
+        }
+        if let Some(cache_dir) = &self.cache_dir {
+            builder = builder.cache_dir(cache_dir);
+        }
+        if let Some(cache) = self.cache {
+            builder = builder.cache(cache);
+        }
+        if let Some(from) = self.from {
+            builder = builder.from(from);
+        }
+        if let Some(until) = self.until {
+            builder = builder.until(until);
+        }
+        if let Some(years) = &self.years {
+            builder = builder.years(years.iter().copied());
+        }
+        if let Some(catid) = self.catid {
+            builder = builder.catid(catid);
+        }
+        if let Some(sections) = &self.sections {
+            builder = builder.sections(sections.iter().cloned());
+        }
+        for transform in &self.transforms {
+            builder = builder.transform(&transform.regex, &transform.replacement);
+        }
+        for (name, template) in &self.plugins {
+            builder = builder.plugin(name, template);
+        }
+        if let Some(sentence_per_line) = self.sentence_per_line {
+            builder = builder.sentence_per_line(sentence_per_line);
+        }
+        if let Some(read_more) = &self.read_more {
+            builder = builder
+                .read_more(Some(read_more.as_str()).filter(|read_more| !read_more.is_empty()));
+        }
+        if let Some(images) = self.images {
+            builder = builder.images(images);
+        }
+        if let Some(thumbnails) = self.thumbnails {
+            builder = builder.thumbnails(thumbnails);
+        }
+        if let Some(bundle_thumbnails) = self.bundle_thumbnails {
+            builder = builder.bundle_thumbnails(bundle_thumbnails);
+        }
+        if let Some(strip_exif) = self.strip_exif {
+            builder = builder.strip_exif(strip_exif);
+        }
+        if let Some(image_names) = &self.image_names {
+            builder = builder.image_names(image_names);
+        }
+        if let Some(slug_dirs) = self.slug_dirs {
+            builder = builder.slug_dirs(slug_dirs);
+        }
+        if let Some(id_dirs) = self.id_dirs {
+            builder = builder.id_dirs(id_dirs);
+        }
+        if let Some(drafts) = self.drafts {
+            builder = builder.drafts(drafts);
+        }
+        if let Some(multilingual) = self.multilingual {
+            builder = builder.multilingual(multilingual);
+        }
+        if let Some(converter_hash) = self.converter_hash {
+            builder = builder.converter_hash(converter_hash);
+        }
+        if let Some(resume) = self.resume {
+            builder = builder.resume(resume);
+        }
+        if let Some(staging) = self.staging {
+            builder = builder.staging(staging);
+        }
+        if let Some(missing_images) = &self.missing_images {
+            builder = builder.missing_images(missing_images.clone());
+        }
+        if let Some(io_limit) = self.io_limit {
+            builder = builder.io_limit(io_limit);
+        }
+        if let Some(existing) = self.existing {
+            builder = builder.existing(existing);
+        }
+        if let Some(authors) = &self.authors {
+            builder = builder.authors(authors);
+        }
+        if let Some(fields) = &self.fields {
+            builder = builder.fields(fields);
+        }
+        if let Some(strings) = &self.strings {
+            builder = builder.strings(strings);
+        }
+        for (catid, name) in &self.categories {
+            builder = builder.category_name(*catid, name);
+        }
+        if let Some(old_url) = &self.old_url {
+            builder = builder.old_url(old_url);
+        }
+        if let Some(rewrite_links) = self.rewrite_links {
+            builder = builder.rewrite_links(rewrite_links);
+        }
+        if let Some(attachments) = self.attachments {
+            builder = builder.attachments(attachments);
+        }
+        builder.target_options(self.target_options.clone())
+    }
+}
+
+/// A table with IDs as keys, which TOML only has as strings.
+fn by_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<u32, String>, D::Error> {
+    HashMap::<String, String>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(id, name)| match id.parse() {
+            Ok(id) => Ok((id, name)),
+            Err(_) => Err(de::Error::custom(format!("{} is no category ID", id))),
+        })
+        .collect()
+}
+
+/// A single path or a list of them.
+fn one_or_many<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<PathBuf>>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(PathBuf),
+        Many(Vec<PathBuf>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(path) => Some(vec![path]),
+        OneOrMany::Many(paths) => Some(paths),
+    })
+}
+
+/// A TOML date like `2024-03-01`, or the same as a string.
+fn date<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<NaiveDate>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Date {
+        Toml(toml::value::Datetime),
+        String(String),
+    }
+    let date = match Date::deserialize(deserializer)? {
+        Date::Toml(date) => date.to_string(),
+        Date::String(date) => date,
+    };
+    date.parse()
+        .map(Some)
+        .map_err(|_| de::Error::custom(format!("{} is no date like 2024-03-01", date)))
+}
+
+/// Extra frontmatter fields by their key, from a TOML file like `type = "einsatz"`.
+pub(crate) fn load_fields(path: &Path) -> Result<BTreeMap<String, Field>, ConfigError> {
+    load_toml(path)
+}
+
+/// The texts written besides the articles, from a TOML file like `strings/en.toml`.
+pub(crate) fn load_strings(path: &Path) -> Result<Strings, ConfigError> {
+    load_toml(path)
+}
+
+fn load_toml<T: DeserializeOwned>(path: &Path) -> Result<T, ConfigError> {
+    let text = fs::read_to_string(path).map_err(|source| ConfigError::ReadConfig {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    toml::from_str(&text).map_err(|error| ConfigError::ParseConfig {
+        path: path.to_path_buf(),
+        message: error.to_string(),
+    })
+}
+
+/// Names of the authors by their user ID, from a TOML file like `62 = "Max Mustermann"`.
+pub(crate) fn load_authors(path: &Path) -> Result<HashMap<u64, String>, ConfigError> {
+    let text = fs::read_to_string(path).map_err(|source| ConfigError::ReadConfig {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let parse_failed = |message| ConfigError::ParseConfig {
+        path: path.to_path_buf(),
+        message,
+    };
+    let names: HashMap<String, String> =
+        toml::from_str(&text).map_err(|error| parse_failed(error.to_string()))?;
+    names
+        .into_iter()
+        .map(|(id, name)| match id.parse() {
+            Ok(id) => Ok((id, name)),
+            Err(_) => Err(parse_failed(format!("{} is no user ID", id))),
+        })
+        .collect()
+}
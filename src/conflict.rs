@@ -0,0 +1,122 @@
+//! Deciding what becomes of the articles an earlier run wrote with a different content,
+//! e.g. edited by hand since, see [`Conflicts`].
+//!
+//! ```no_run
+//! use ff_website_converter::{
+//!     conflict::{Conflict, Resolution},
+//!     output::Existing,
+//!     Converter,
+//! };
+//!
+//! let mut converter = Converter::default();
+//! converter.existing = Existing::Ask;
+//! converter.conflicts = Some(Box::new(|conflict: &Conflict| {
+//!     print!("{}", conflict.diff());
+//!     Resolution::Keep
+//! }));
+//! converter.convert()?;
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+use crate::error::ArticleRef;
+use std::path::Path;
+
+/// How many unchanged lines [`Conflict::diff`] shows around the changed ones.
+const DIFF_CONTEXT: usize = 2;
+
+/// An article file that is there already with another content than the conversion
+/// writes.
+#[derive(Debug, Clone)]
+pub struct Conflict<'a> {
+    pub article: ArticleRef,
+    /// Path of the article file in the output directory.
+    pub path: &'a Path,
+    /// What the file holds.
+    pub existing: &'a str,
+    /// What the conversion writes instead.
+    pub output: &'a str,
+}
+
+impl Conflict<'_> {
+    /// The lines of the file that the conversion changes, `-` for the removed and `+` for
+    /// the added ones, with the unchanged lines around them. Unchanged lines further away
+    /// are cut to `...`.
+    pub fn diff(&self) -> String {
+        let existing: Vec<&str> = self.existing.lines().collect();
+        let output: Vec<&str> = self.output.lines().collect();
+        // Lengths of the longest common subsequences of the ends of both, from the back.
+        let mut common = vec![vec![0usize; output.len() + 1]; existing.len() + 1];
+        for (i, line) in existing.iter().enumerate().rev() {
+            for (j, other) in output.iter().enumerate().rev() {
+                common[i][j] = if line == other {
+                    common[i + 1][j + 1] + 1
+                } else {
+                    common[i + 1][j].max(common[i][j + 1])
+                };
+            }
+        }
+        let mut lines = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < existing.len() || j < output.len() {
+            if i < existing.len() && j < output.len() && existing[i] == output[j] {
+                lines.push((' ', existing[i]));
+                i += 1;
+                j += 1;
+            } else if j == output.len()
+                || (i < existing.len() && common[i + 1][j] >= common[i][j + 1])
+            {
+                lines.push(('-', existing[i]));
+                i += 1;
+            } else {
+                lines.push(('+', output[j]));
+                j += 1;
+            }
+        }
+
+        let changed: Vec<usize> = (0..lines.len())
+            .filter(|index| lines[*index].0 != ' ')
+            .collect();
+        let is_shown = |index: usize| {
+            changed
+                .iter()
+                .any(|changed| changed.abs_diff(index) <= DIFF_CONTEXT)
+        };
+        let mut diff = String::new();
+        let mut cut = false;
+        for (index, (mark, line)) in lines.iter().enumerate() {
+            if is_shown(index) {
+                diff.push_str(format!("{} {}", mark, line).trim_end());
+                diff.push('\n');
+                cut = false;
+            } else if !cut {
+                diff.push_str("...\n");
+                cut = true;
+            }
+        }
+        diff
+    }
+}
+
+/// What becomes of the article file of a [`Conflict`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Leaves the file as it is.
+    Keep,
+    /// Writes the article over it.
+    Overwrite,
+}
+
+/// Resolves the [`Conflict`]s of [`Converter::convert`](crate::Converter::convert), from
+/// the worker threads. Without, the articles are kept.
+pub trait Conflicts: Send + Sync {
+    fn resolve(&self, conflict: &Conflict) -> Resolution;
+}
+
+impl<F> Conflicts for F
+where
+    F: Fn(&Conflict) -> Resolution + Send + Sync,
+{
+    fn resolve(&self, conflict: &Conflict) -> Resolution {
+        self(conflict)
+    }
+}
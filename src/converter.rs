@@ -0,0 +1,1032 @@
+use crate::{
+    builder::ConverterBuilder,
+    checkpoint::Checkpoint,
+    conflict::Conflicts,
+    download::{self, Downloads},
+    error::ConverterError,
+    hook::ArticleHook,
+    html,
+    images::{self, ImageCache, ImageEncoding, ThumbnailSize, QUALITY},
+    links,
+    model::{self, Article, ArticleKey, ArticleNumber, Image, ImageFormat, Section},
+    output::{self, Existing, MissingImages, YearArticles},
+    plugins,
+    progress::Progress,
+    redirects,
+    render::{self, RenderOptions, RenderedArticle},
+    report::{self, ArticleReport, ArticleStatus, MissingImage, Report},
+    source::{ArticleSource, JoomlaJson, SourceArticle, State},
+    target::{Hugo, OutputTarget},
+    throttle::Throttle,
+    timings::{Phase, Timings},
+    transform::{Pipeline, TextCache},
+};
+use chrono::NaiveDateTime;
+use rayon::prelude::*;
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{mpsc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+pub(crate) const INPUT_FILE: &str = "missions.json";
+pub(crate) const OLD_WEBSITE_DIR: &str = "website.old";
+pub(crate) const OUTPUT_DIR: &str = "output";
+pub(crate) const CACHE_DIR: &str = ".ffwc-cache";
+pub(crate) const YEARS: [u32; 4] = [2021, 2020, 2019, 2018];
+pub(crate) const CATID: u32 = 5;
+/// Hugo's summary divider, the intro of an article becomes its summary.
+pub(crate) const READ_MORE: &str = "<!--more-->";
+
+/// Decides which articles a [`Converter`] writes.
+pub type Select = Box<dyn Fn(&ArticleKey) -> bool + Send + Sync>;
+
+/// Settings of a conversion from a CMS export into a static site content tree.
+///
+/// The defaults match the layout of the original migration: `missions.json` and
+/// `website.old/` in the working directory, Hugo output into `output/`.
+pub struct Converter {
+    pub source: Box<dyn ArticleSource>,
+    pub target: Box<dyn OutputTarget>,
+    /// Root of the old website, image paths in the articles are relative to it.
+    pub old_website_dir: PathBuf,
+    pub output_dir: PathBuf,
+    /// Where cleaned texts of earlier runs are kept.
+    pub cache_dir: PathBuf,
+    /// Keeps the cleaned texts and processed images in [`cache_dir`](Self::cache_dir) and
+    /// uses the ones of earlier runs. Without it, every article and image is processed again.
+    pub cache: bool,
+    /// Years to convert, each one becomes its own Hugo section.
+    pub years: Vec<u32>,
+    /// The Joomla categories of the articles to convert, with where they go.
+    pub sections: Vec<Section>,
+    /// The text cleanup applied to every article.
+    pub pipeline: Pipeline,
+    /// Put on its own line between the intro and the full text of the articles that
+    /// have one, `None` to join them.
+    pub read_more: Option<String>,
+    /// Re-encodes the images, `None` copies them as they are.
+    pub images: Option<ImageEncoding>,
+    /// Scales the thumbnails, in the format of the images, `None` copies the first image
+    /// like the others.
+    pub thumbnails: Option<ThumbnailSize>,
+    /// Writes the thumbnails into the article directories as `thumbnail.jpg` and the
+    /// like, which the frontmatter refers to as a page resource, instead of the separate
+    /// `thumbnail` tree, see [`Article::bundle_thumbnail`].
+    pub bundle_thumbnails: bool,
+    /// Pattern of the file names of the images with the
+    /// [`IMAGE_NAME_PLACEHOLDERS`](model::IMAGE_NAME_PLACEHOLDERS), like
+    /// `{slug}-{image}.{ext}`. `None` for the [`DEFAULT_IMAGE_NAMES`](model::DEFAULT_IMAGE_NAMES).
+    /// The names have to stay unique within the article directory.
+    pub image_names: Option<String>,
+    /// Removes the EXIF metadata, e.g. GPS positions, of the images that are copied as
+    /// they are and turns them upright, see [`images::strip_exif`]. The re-encoded
+    /// images and thumbnails are upright and without it anyway.
+    pub strip_exif: bool,
+    /// Names the article directories, thumbnails and images after the date and title of
+    /// the articles, e.g. `2019-05-04-brandmeldealarm`, instead of their numbers.
+    pub slug_dirs: bool,
+    /// Names the article directories, thumbnails and images after the IDs of the articles
+    /// in the source, e.g. `104`, instead of their numbers, which an article added to a
+    /// year later renumbers. Takes precedence over [`slug_dirs`](Self::slug_dirs), the
+    /// articles without an ID are named after their slug.
+    pub id_dirs: bool,
+    /// What to do with the images missing in the old website.
+    pub missing_images: MissingImages,
+    /// Bandwidth limit for all reads and downloads of images and attachments.
+    pub throttle: Option<Throttle>,
+    /// Run on every article in order, after the cleanup.
+    pub hooks: Vec<Box<dyn ArticleHook>>,
+    /// What to do with the output of an earlier run, by default its years are skipped.
+    pub existing: Existing,
+    /// Only the articles this returns `true` for are written, e.g. the approved ones
+    /// of a [`Review`](crate::review::Review). The others keep their numbers.
+    pub select: Option<Select>,
+    /// Told about every article and image written.
+    pub progress: Option<Box<dyn Progress>>,
+    /// Decides what becomes of the article files that an earlier run left with another
+    /// content, see [`Existing::Ask`]. Without, they are kept.
+    pub conflicts: Option<Box<dyn Conflicts>>,
+    /// Names of the authors by their [`SourceArticle::author_id`], for the articles not
+    /// signed with a name of their own.
+    pub authors: HashMap<u64, String>,
+    /// Names of the categories by their ID, for the sources that only have the ID.
+    pub categories: HashMap<u32, String>,
+    /// Scheme of the article URLs of the old website, see [`redirects::old_url`]. The
+    /// URL of every article goes into its [`aliases`](crate::model::Article::aliases).
+    pub old_url: Option<String>,
+    /// Converts the unpublished articles as drafts, which Hugo only builds with
+    /// `--buildDrafts`, instead of leaving them out. Trashed articles are always left out.
+    pub drafts: bool,
+    /// Writes the articles in a language as `index.de.md` and the like, for a multilingual
+    /// site. The translations of an article of the same year, as told by the Joomla
+    /// associations, are written into its directory and section, whatever their category.
+    pub multilingual: bool,
+    /// Writes the [`SourceArticle::hash`] of every article into its frontmatter as the
+    /// [`Article::converter_hash`](crate::model::Article::converter_hash). An article
+    /// file with one is only written again by [`Existing::UpdateChanged`] if its source
+    /// changed since, not for other settings, so that the new website can follow the
+    /// edits of the old one without losing its own.
+    pub converter_hash: bool,
+    /// Continues a conversion that crashed or was stopped: its partial years are converted
+    /// again, leaving out the articles it wrote completely, as recorded in the
+    /// [`CHECKPOINT_FILE`](output::CHECKPOINT_FILE). Without it, the checkpoint starts over.
+    pub resume: bool,
+    /// Writes the years that are written from scratch into the
+    /// [`STAGING_DIR`](output::STAGING_DIR) and moves each into place once all of its
+    /// articles have been written, see [`YearArticles::stage`]. A failed run then leaves
+    /// the output as it was instead of half-written years.
+    pub staging: bool,
+    /// Rewrites the links between the articles, like `index.php?option=com_content&id=123`
+    /// of Joomla, into links of the [target](OutputTarget::article_link) to the converted
+    /// articles, see [`links::rewrite_links`]. The links to articles that are not
+    /// converted are kept and reported.
+    pub rewrite_links: bool,
+    /// Copies the documents the articles link to, like the PDFs of reports, into the
+    /// [`ATTACHMENT_DIR`](model::ATTACHMENT_DIR) of their directories and points the links
+    /// to them, see [`links::link_attachments`]. Documents missing in the old website keep
+    /// their links and are reported.
+    pub attachments: bool,
+}
+
+impl Default for Converter {
+    fn default() -> Self {
+        Converter {
+            source: Box::new(JoomlaJson::new(INPUT_FILE)),
+            target: Box::new(Hugo::default()),
+            old_website_dir: PathBuf::from(OLD_WEBSITE_DIR),
+            output_dir: PathBuf::from(OUTPUT_DIR),
+            cache_dir: PathBuf::from(CACHE_DIR),
+            cache: true,
+            years: YEARS.to_vec(),
+            sections: vec![Section::new(CATID)],
+            pipeline: Pipeline::builtin(),
+            read_more: Some(READ_MORE.to_string()),
+            images: None,
+            thumbnails: None,
+            bundle_thumbnails: false,
+            image_names: None,
+            strip_exif: false,
+            slug_dirs: false,
+            id_dirs: false,
+            missing_images: MissingImages::default(),
+            throttle: None,
+            hooks: Vec::new(),
+            existing: Existing::default(),
+            select: None,
+            progress: None,
+            conflicts: None,
+            authors: HashMap::new(),
+            categories: HashMap::new(),
+            old_url: None,
+            drafts: false,
+            multilingual: false,
+            converter_hash: false,
+            resume: false,
+            staging: false,
+            rewrite_links: false,
+            attachments: false,
+        }
+    }
+}
+
+/// The outcome of [`Converter::convert`].
+pub struct Conversion {
+    /// Where the time was spent.
+    pub timings: Timings,
+    /// The articles that could not be written, by path. All others were written.
+    pub failures: Vec<ConverterError>,
+    /// The images skipped or replaced by the placeholder, see [`Converter::missing_images`],
+    /// by article.
+    pub missing_images: Vec<MissingImage>,
+    /// Every article of the configured years and sections, in the order of the preview,
+    /// with what became of it.
+    pub report: Report,
+}
+
+/// Puts the translations of an article into its category, that of the earliest article
+/// in a section of those with the same association in the same year, and drops the
+/// articles that are still in none.
+fn join_translations(keys: &mut Vec<ArticleKey>, in_section: impl Fn(Option<u32>) -> bool) {
+    let mut categories: HashMap<(String, u32), (NaiveDateTime, usize, Option<u32>)> =
+        HashMap::new();
+    for key in keys.iter().filter(|key| in_section(key.catid)) {
+        let Some(association) = &key.association else {
+            continue;
+        };
+        let candidate = (key.date, key.ordinal, key.catid);
+        categories
+            .entry((association.clone(), key.year))
+            .and_modify(|earliest| *earliest = (*earliest).min(candidate))
+            .or_insert(candidate);
+    }
+    for key in keys.iter_mut() {
+        if let Some(association) = &key.association {
+            if let Some((_, _, catid)) = categories.get(&(association.clone(), key.year)) {
+                key.catid = *catid;
+            }
+        }
+    }
+    keys.retain(|key| in_section(key.catid));
+}
+
+/// Shared state of a conversion run, handed to every worker.
+pub(crate) struct Context<'a> {
+    pub converter: &'a Converter,
+    pub timings: Timings,
+    /// `None` without [`Converter::cache`].
+    pub text_cache: Option<TextCache>,
+    pub image_cache: Option<ImageCache>,
+    pub missing_images: Mutex<Vec<MissingImage>>,
+    pub reports: Mutex<Vec<ArticleReport>>,
+    pub checkpoint: Checkpoint,
+    pub downloads: Downloads,
+}
+
+impl Context<'_> {
+    /// Path of the image file `source`, the path in the old website or a downloaded image.
+    /// A URL that could not be downloaded stays as it is.
+    pub fn image_path(&self, source: &Path) -> PathBuf {
+        if download::is_remote(source) {
+            source.to_path_buf()
+        } else {
+            self.converter.old_website_dir.join(source)
+        }
+    }
+
+    /// Downloads the images of `article` that are linked with a URL and points them to the
+    /// downloaded files. The ones that fail to download are left to
+    /// [`replace_missing_images`](Self::replace_missing_images).
+    fn download_images(&self, article: &mut Article) {
+        let mut remote: Vec<&mut Image> = article
+            .images
+            .iter_mut()
+            .filter(|image| download::is_remote(&image.source))
+            .collect();
+        if remote.is_empty() {
+            return;
+        }
+        let urls: Vec<String> = remote
+            .iter()
+            .map(|image| image.source.to_string_lossy().into_owned())
+            .collect();
+        let downloaded = self.timings.time(Phase::Images, || {
+            self.downloads
+                .fetch_all(&urls, self.converter.throttle.as_ref())
+        });
+        for ((image, url), downloaded) in remote.iter_mut().zip(&urls).zip(downloaded) {
+            match downloaded.and_then(std::path::absolute) {
+                Ok(path) => image.source = path,
+                Err(error) => tracing::warn!("Failed to download {}: {}", url, error),
+            }
+        }
+    }
+
+    /// Skips or replaces the images of `article` that are missing in the old website,
+    /// see [`Converter::missing_images`]. Returns their paths.
+    fn replace_missing_images(
+        &self,
+        article: &mut Article,
+        year: u32,
+        article_index: usize,
+    ) -> Vec<PathBuf> {
+        let converter = self.converter;
+        if converter.missing_images == MissingImages::Fail {
+            return Vec::new();
+        }
+        let mut missing = Vec::new();
+        article.images.retain_mut(|image| {
+            let path = self.image_path(&image.source);
+            if path.is_file() {
+                return true;
+            }
+            tracing::debug!(
+                "Image {} of article {}-{} {} is missing",
+                path.display(),
+                year,
+                ArticleNumber(article_index),
+                article.title
+            );
+            missing.push(path);
+            match &converter.missing_images {
+                MissingImages::Placeholder(placeholder) => {
+                    image.source = placeholder.clone();
+                    true
+                }
+                _ => false,
+            }
+        });
+        let missing_images = missing.iter().map(|path| {
+            MissingImage::new(
+                &article.section,
+                year,
+                article_index,
+                &article.title,
+                path.clone(),
+            )
+        });
+        self.missing_images.lock().unwrap().extend(missing_images);
+        missing
+    }
+
+    /// Copies or re-encodes an image, see [`Converter::images`].
+    pub fn copy_image(&self, source: &Path, destination: &Path) -> io::Result<u64> {
+        if self.is_kept(source, destination) {
+            return Ok(0);
+        }
+        let strip_exif = self.converter.strip_exif;
+        match (&self.converter.images, &self.converter.throttle) {
+            (Some(encoding), _) => {
+                let settings = format!("{:?} {} {}", encoding.format, encoding.quality, strip_exif);
+                self.process_image(source, destination, &settings, |content| {
+                    encoding.encode(content, destination)
+                })
+            }
+            (None, _) if strip_exif => {
+                self.process_image(source, destination, "strip-exif", |content| {
+                    images::strip_exif(content, destination)
+                })
+            }
+            (None, Some(throttle)) => throttle.copy(source, destination),
+            (None, None) => fs::copy(source, destination),
+        }
+    }
+
+    /// Copies an attachment as it is, see [`Converter::attachments`].
+    pub fn copy_attachment(&self, source: &Path, destination: &Path) -> io::Result<u64> {
+        if self.is_kept(source, destination) {
+            return Ok(0);
+        }
+        match &self.converter.throttle {
+            Some(throttle) => throttle.copy(source, destination),
+            None => fs::copy(source, destination),
+        }
+    }
+
+    /// Copies or scales the first image of an article, see [`Converter::thumbnails`]. The
+    /// scaled thumbnail is written in `format`, that of the image.
+    pub fn copy_thumbnail(
+        &self,
+        source: &Path,
+        destination: &Path,
+        format: ImageFormat,
+    ) -> io::Result<u64> {
+        let Some(size) = self.converter.thumbnails else {
+            return self.copy_image(source, destination);
+        };
+        if self.is_kept(source, destination) {
+            return Ok(0);
+        }
+        let quality = self
+            .converter
+            .images
+            .map_or(QUALITY, |encoding| encoding.quality);
+        let settings = format!(
+            "{:?} {} {}x{} {}",
+            format, quality, size.width, size.height, self.converter.strip_exif
+        );
+        self.process_image(source, destination, &settings, |content| {
+            ImageEncoding { format, quality }.thumbnail(content, destination, size)
+        })
+    }
+
+    /// Runs `process` on the content of `source` to write it processed with `settings` to
+    /// `destination`, unless the [`ImageCache`] has its output of an earlier run. A copy
+    /// of that output made after the last change of `source` stays with
+    /// [`Existing::UpdateChanged`] and [`Existing::Ask`].
+    fn process_image(
+        &self,
+        source: &Path,
+        destination: &Path,
+        settings: &str,
+        process: impl FnOnce(&[u8]) -> io::Result<u64>,
+    ) -> io::Result<u64> {
+        let content = self.read_image(source)?;
+        let Some(image_cache) = &self.image_cache else {
+            return process(&content);
+        };
+        let entry = image_cache.entry(&content, settings, destination);
+        let kept = matches!(
+            self.converter.existing,
+            Existing::UpdateChanged | Existing::Ask
+        ) && is_up_to_date(source, &entry, destination);
+        if kept {
+            tracing::debug!("Image {} is up to date, skipped", destination.display());
+            return Ok(0);
+        }
+        image_cache.write(&entry, destination, || process(&content))
+    }
+
+    /// Reads the image at `source` within the [`Converter::throttle`].
+    fn read_image(&self, source: &Path) -> io::Result<Vec<u8>> {
+        match &self.converter.throttle {
+            Some(throttle) => throttle.read(source),
+            None => fs::read(source),
+        }
+    }
+
+    /// Tells the images of `article` that are copied as they are their format, as their
+    /// content says, so that they keep it instead of the extension of the re-encoded ones.
+    fn detect_image_formats(&self, article: &mut Article) {
+        if self.converter.images.is_some() {
+            return;
+        }
+        for image in &mut article.images {
+            // The missing images fail or are left out later.
+            let format = images::detect_format(&self.image_path(&image.source));
+            image.format = format
+                .ok()
+                .flatten()
+                .filter(|format| *format != article.image_format);
+        }
+    }
+
+    /// Whether the copy of `source` an earlier run left at `destination` stays, see
+    /// [`Converter::existing`].
+    fn is_kept(&self, source: &Path, destination: &Path) -> bool {
+        let kept = match self.converter.existing {
+            Existing::SkipYears | Existing::Overwrite => false,
+            Existing::SkipExisting => destination.exists(),
+            Existing::UpdateChanged | Existing::Ask => is_up_to_date(source, source, destination),
+        };
+        if kept {
+            tracing::debug!("Image {} is up to date, skipped", destination.display());
+        }
+        kept
+    }
+}
+
+impl Converter {
+    /// Starts from the defaults, see [`ConverterBuilder`].
+    pub fn builder() -> ConverterBuilder {
+        ConverterBuilder::default()
+    }
+
+    /// Format of the images in the output.
+    pub fn image_format(&self) -> ImageFormat {
+        self.images
+            .map(|encoding| encoding.format)
+            .unwrap_or_default()
+    }
+
+    /// Converts all articles of the configured years and categories, running on the
+    /// current rayon thread pool. An article that fails does not stop the others, its
+    /// error is collected and its year is left [partial](crate::output::PARTIAL_MARKER).
+    /// Only failures to read the source or to prepare the years end the conversion.
+    pub fn convert(&self) -> Result<Conversion, ConverterError> {
+        let context = Context {
+            converter: self,
+            timings: Timings::default(),
+            text_cache: self
+                .cache
+                .then(|| TextCache::new(self.cache_dir.join("text"))),
+            image_cache: self
+                .cache
+                .then(|| ImageCache::new(self.cache_dir.join("processed"))),
+            missing_images: Mutex::default(),
+            reports: Mutex::default(),
+            checkpoint: Checkpoint::open(&self.output_dir, self.resume)?,
+            downloads: Downloads::new(self.cache_dir.join("images")),
+        };
+        let timings = &context.timings;
+
+        let (keys, invalid) = self.keys(timings)?;
+        output::write_configs(&self.output_dir, &*self.target, &self.sections, &self.years)?;
+        let mut years: Vec<YearArticles> = Vec::new();
+        // Only read for the report.
+        let mut skipped_years: Vec<YearArticles> = Vec::new();
+        for section in &self.sections {
+            for &year in &self.years {
+                let mut year_articles = YearArticles::new(&keys, section, year);
+                if let Some(select) = &self.select {
+                    year_articles.select(select);
+                }
+                if self.id_dirs {
+                    year_articles.name_by_id();
+                } else if self.slug_dirs {
+                    year_articles.name_by_slug();
+                }
+                if self.bundle_thumbnails {
+                    year_articles.bundle_thumbnails();
+                }
+                if self.staging
+                    && matches!(self.existing, Existing::SkipYears | Existing::Overwrite)
+                {
+                    year_articles.stage();
+                }
+                if year_articles.prepare(
+                    &self.output_dir,
+                    &*self.target,
+                    self.existing,
+                    self.resume,
+                )? {
+                    years.push(year_articles);
+                } else {
+                    skipped_years.push(year_articles);
+                }
+            }
+        }
+
+        // The IDs of the first pass are enough to find the articles the links point to.
+        let article_paths: HashMap<u64, String> = if self.rewrite_links {
+            years
+                .iter()
+                .chain(&skipped_years)
+                .flat_map(YearArticles::article_paths)
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        // The articles to write, and the skipped ones for the report.
+        let mut positions: HashMap<usize, (&YearArticles, usize, bool)> = HashMap::new();
+        for year_articles in &years {
+            for (article_index, key) in year_articles.entries() {
+                let selected = year_articles.is_selected(article_index);
+                positions.insert(key.ordinal, (year_articles, article_index, selected));
+            }
+        }
+        for year_articles in &skipped_years {
+            for (article_index, key) in year_articles.entries() {
+                positions.insert(key.ordinal, (year_articles, article_index, false));
+            }
+        }
+
+        if let Some(progress) = &self.progress {
+            progress.start(
+                positions
+                    .values()
+                    .filter(|(_, _, selected)| *selected)
+                    .count(),
+            );
+        }
+
+        // Second pass: articles flow one by one through parse, clean and write. The bounded
+        // channel keeps only a few of them in memory while the reader is ahead of the writers.
+        let (sender, receiver) = mpsc::sync_channel(2 * rayon::current_num_threads());
+        let mut failures = thread::scope(|scope| {
+            let (positions, context, article_paths) = (&positions, &context, &article_paths);
+            let reader = scope.spawn(move || {
+                // Collected by the first pass already.
+                self.stream_timed(&context.timings, &mut Vec::new(), |article| {
+                    if let Some(&(year_articles, article_index, selected)) =
+                        positions.get(&article.ordinal)
+                    {
+                        // The receiver only goes away if a writer panicked.
+                        let _ = sender.send((year_articles, article_index, selected, article));
+                    }
+                })
+            });
+
+            let convert_article = |(year_articles, article_index, selected, source_article): (
+                &YearArticles,
+                usize,
+                bool,
+                _,
+            )| {
+                let article_start = Instant::now();
+                let read_more = self.read_more.as_deref();
+                let mut article = get_article(&source_article, read_more, |html| {
+                    context
+                        .timings
+                        .time(Phase::Clean, || match &context.text_cache {
+                            Some(text_cache) => text_cache.clean(&self.pipeline, html),
+                            None => self.pipeline.apply(html).into_owned(),
+                        })
+                });
+                // Before the images are downloaded or replaced, whose paths then depend on
+                // the cache directory and what is missing.
+                if self.converter_hash {
+                    article.converter_hash = Some(source_article.hash(&article.images));
+                }
+                article.aliases = self
+                    .old_url
+                    .iter()
+                    .filter_map(|scheme| redirects::old_url(scheme, &source_article))
+                    .collect();
+                article.section = year_articles.section.dir.clone();
+                article.language = source_article
+                    .language
+                    .as_deref()
+                    .filter(|_| self.multilingual)
+                    .map(model::language_code);
+                let mut report = ArticleReport::new(&article, year_articles.year, article_index);
+                report.file =
+                    Some(year_articles.article_file(&article, article_index, &*self.target));
+                if !selected {
+                    report.status = Some(ArticleStatus::Skipped);
+                    context.reports.lock().unwrap().push(report);
+                    return Ok(());
+                }
+                article.image_format = self.image_format();
+                article.image_names = self.image_names.clone();
+                article.bundle_thumbnail = self.bundle_thumbnails;
+                article.dir = year_articles.dir(article_index).map(str::to_string);
+                for hook in &self.hooks {
+                    hook.apply(&mut article);
+                }
+                let missing_attachments = if self.attachments {
+                    links::link_attachments(&mut article, |source| {
+                        self.old_website_dir.join(source).is_file()
+                    })
+                } else {
+                    Vec::new()
+                };
+                let mut unresolved = Vec::new();
+                if self.rewrite_links {
+                    (article.text, unresolved) =
+                        links::rewrite_links(&article.text, article_paths, &*self.target);
+                }
+                context.download_images(&mut article);
+                let missing =
+                    context.replace_missing_images(&mut article, year_articles.year, article_index);
+                context.detect_image_formats(&mut article);
+                report.images = article.images.len();
+                report.warnings = missing
+                    .iter()
+                    .map(|path| format!("missing image {}", path.display()))
+                    .collect();
+                for source in missing_attachments {
+                    tracing::warn!(
+                        "Attachment {} of article {}-{} {} is missing",
+                        source.display(),
+                        year_articles.label(),
+                        ArticleNumber(article_index),
+                        article.title
+                    );
+                    report
+                        .warnings
+                        .push(format!("missing attachment {}", source.display()));
+                }
+                for url in unresolved {
+                    tracing::warn!(
+                        "Link {} of article {}-{} {} points to an article that is not converted",
+                        url,
+                        year_articles.label(),
+                        ArticleNumber(article_index),
+                        article.title
+                    );
+                    report.warnings.push(format!("unresolved link {}", url));
+                }
+                if article.text.trim().is_empty() {
+                    report.warnings.push("empty text".to_string());
+                }
+                for name in plugins::unknown_tags(&article.text) {
+                    tracing::warn!(
+                        "Article {}-{} {} has a tag of the unknown plugin {}",
+                        year_articles.label(),
+                        ArticleNumber(article_index),
+                        article.title,
+                        name
+                    );
+                    report.warnings.push(format!("unknown plugin {}", name));
+                }
+                report.losses = report::lossy(&source_article.html);
+                if !report.losses.is_empty() {
+                    tracing::debug!(
+                        "Article {}-{} {} lost {}",
+                        year_articles.label(),
+                        ArticleNumber(article_index),
+                        article.title,
+                        report.losses.join(", ")
+                    );
+                }
+                let hash = article.content_hash();
+                // After the hooks, which may change it.
+                let path = year_articles.article_file(&article, article_index, &*self.target);
+                report.file = Some(path.clone());
+                if context.checkpoint.is_done(&path, &hash) {
+                    tracing::debug!("Article {} was written by the resumed run", path.display());
+                    report.status = Some(ArticleStatus::Skipped);
+                    context.reports.lock().unwrap().push(report);
+                    if let Some(progress) = &self.progress {
+                        progress.article_started(0);
+                        progress.article_done();
+                    }
+                    return year_articles.article_done(&self.output_dir);
+                }
+                if self.existing == Existing::UpdateChanged {
+                    article.source_hash = Some(hash.clone());
+                }
+                if let Some(progress) = &self.progress {
+                    progress.article_started(article.images.len());
+                }
+                let converted = year_articles
+                    .convert_article(&article, article_index, context)
+                    .and_then(|()| context.checkpoint.complete(&article, &path, &hash));
+                if let Some(progress) = &self.progress {
+                    progress.article_done();
+                }
+                report.status = Some(match &converted {
+                    Ok(()) => ArticleStatus::Converted,
+                    Err(_) => ArticleStatus::Failed,
+                });
+                report.error = converted.as_ref().err().map(ToString::to_string);
+                context.reports.lock().unwrap().push(report);
+                converted?;
+                context.timings.record_article(
+                    format!(
+                        "{}-{} {}",
+                        year_articles.label(),
+                        ArticleNumber(article_index),
+                        article.title
+                    ),
+                    article_start.elapsed(),
+                );
+                Ok(())
+            };
+            let mut failures: Vec<ConverterError> = receiver
+                .into_iter()
+                .par_bridge()
+                .filter_map(|article| convert_article(article).err())
+                .collect();
+            failures.sort_by(|a, b| a.path().cmp(b.path()));
+            if let Some(progress) = &self.progress {
+                progress.finish();
+            }
+
+            reader.join().expect("Input reader panicked")?;
+            Ok::<_, ConverterError>(failures)
+        })?;
+
+        if failures.is_empty() {
+            context
+                .checkpoint
+                .remove()
+                .map_err(|source| ConverterError::WriteFailed {
+                    article: None,
+                    path: self.output_dir.join(output::CHECKPOINT_FILE),
+                    source,
+                })?;
+            // Only the empty directories of the years moved into place are left.
+            let staging_dir = self.output_dir.join(output::STAGING_DIR);
+            if self.staging && staging_dir.exists() {
+                fs::remove_dir_all(&staging_dir).map_err(|source| ConverterError::WriteFailed {
+                    article: None,
+                    path: staging_dir.clone(),
+                    source,
+                })?;
+            }
+        }
+        let mut missing_images = context.missing_images.into_inner().unwrap();
+        missing_images
+            .sort_by(|a, b| (&a.section, a.year, &a.number).cmp(&(&b.section, b.year, &b.number)));
+        let mut articles = context.reports.into_inner().unwrap();
+        articles.sort_by_key(|report| {
+            let section = self
+                .sections
+                .iter()
+                .position(|section| section.dir == report.section);
+            let year = self.years.iter().position(|year| *year == report.year);
+            (section, year, report.number.clone())
+        });
+        // The invalid entries of the source are in no year, they follow the articles.
+        articles.extend(invalid.iter().map(ArticleReport::invalid));
+        failures.extend(invalid);
+        Ok(Conversion {
+            timings: context.timings,
+            failures,
+            missing_images,
+            report: Report { articles },
+        })
+    }
+
+    /// Writes the section tree of the configured years and categories without the
+    /// articles, see [`output::write_scaffold`]. Returns the written files.
+    pub fn scaffold(&self) -> Result<Vec<PathBuf>, ConverterError> {
+        output::write_scaffold(&self.output_dir, &*self.target, &self.sections, &self.years)
+    }
+
+    /// Renders all articles of the configured years and categories in memory, numbered as
+    /// [`convert`](Self::convert) numbers them, next to their source. Nothing is written
+    /// and the selection is ignored.
+    pub fn preview(&self) -> Result<Vec<(SourceArticle, RenderedArticle)>, ConverterError> {
+        let timings = Timings::default();
+        let (keys, invalid) = self.keys(&timings)?;
+        for error in invalid {
+            tracing::warn!("{}", error);
+        }
+        // Every category belongs to one section, so the ordinal is enough to find both.
+        let mut positions: HashMap<usize, (usize, usize, Option<String>)> = HashMap::new();
+        let mut article_paths: HashMap<u64, String> = HashMap::new();
+        for (section_index, section) in self.sections.iter().enumerate() {
+            for &year in &self.years {
+                let mut year_articles = YearArticles::new(&keys, section, year);
+                if self.id_dirs {
+                    year_articles.name_by_id();
+                } else if self.slug_dirs {
+                    year_articles.name_by_slug();
+                }
+                for (article_index, key) in year_articles.entries() {
+                    let dir = year_articles.dir(article_index).map(str::to_string);
+                    positions.insert(key.ordinal, (section_index, article_index, dir));
+                }
+                if self.rewrite_links {
+                    article_paths.extend(year_articles.article_paths());
+                }
+            }
+        }
+
+        let mut previews = Vec::new();
+        let mut error = None;
+        // The invalid entries were told about in the first pass.
+        self.source.stream_all(
+            &mut |mut source| {
+                self.fill_names(&mut source);
+                if let (None, Some((section, index, dir))) =
+                    (&error, positions.get(&source.ordinal))
+                {
+                    let options = RenderOptions {
+                        target: &*self.target,
+                        pipeline: &self.pipeline,
+                        hooks: &self.hooks,
+                        section: self.sections[*section].dir.as_deref(),
+                        read_more: self.read_more.as_deref(),
+                        image_format: self.image_format(),
+                        image_names: self.image_names.as_deref(),
+                        bundle_thumbnails: self.bundle_thumbnails,
+                        index: *index,
+                        dir: dir.as_deref(),
+                        old_url: self.old_url.as_deref(),
+                        multilingual: self.multilingual,
+                        attachments: self.attachments,
+                        article_paths: Some(&article_paths).filter(|_| self.rewrite_links),
+                    };
+                    match render::convert_article(&source, &options) {
+                        Ok(rendered) => previews.push((source, rendered)),
+                        Err(render_error) => error = Some(render_error),
+                    }
+                }
+            },
+            &mut |_| {},
+        )?;
+        if let Some(error) = error {
+            return Err(error);
+        }
+
+        // In the order of the sections and years, then by number.
+        previews.sort_by_key(|(_, rendered)| {
+            let section = self
+                .sections
+                .iter()
+                .position(|section| section.dir == rendered.article.section);
+            let year = self.years.iter().position(|year| *year == rendered.year);
+            (section, year, rendered.path.clone())
+        });
+        Ok(previews)
+    }
+
+    /// First pass: only keep the sort keys of the matching articles to number them, and
+    /// the errors of the entries of the source that are not valid articles.
+    fn keys(
+        &self,
+        timings: &Timings,
+    ) -> Result<(Vec<ArticleKey>, Vec<ConverterError>), ConverterError> {
+        let in_section = |catid: Option<u32>| {
+            self.sections
+                .iter()
+                .any(|section| catid == Some(section.catid))
+        };
+        let mut keys: Vec<ArticleKey> = Vec::new();
+        let mut invalid = Vec::new();
+        self.stream_timed(timings, &mut invalid, |article| {
+            timings.time(Phase::Filter, || {
+                let mut key = ArticleKey::new(&article);
+                if !self.multilingual {
+                    key.association = None;
+                }
+                // Translations may be in a category of their language, they join the
+                // section of the article they translate below.
+                let kept = in_section(key.catid) || key.association.is_some();
+                let converted = article.state.is_converted(self.drafts);
+                if kept && converted && self.years.contains(&key.year) {
+                    keys.push(key);
+                }
+            })
+        })?;
+        if self.multilingual {
+            join_translations(&mut keys, in_section);
+        }
+        Ok((keys, invalid))
+    }
+
+    /// Names the author and the category of `source` from [`authors`](Self::authors)
+    /// and [`categories`](Self::categories), if the source has no names.
+    pub(crate) fn fill_names(&self, source: &mut SourceArticle) {
+        if source.author.is_none() {
+            source.author = source
+                .author_id
+                .and_then(|id| self.authors.get(&id))
+                .cloned();
+        }
+        if source.categories.is_empty() {
+            source.categories = source
+                .catid
+                .and_then(|catid| self.categories.get(&catid))
+                .cloned()
+                .into_iter()
+                .collect();
+        }
+    }
+
+    /// Streams the source, booking the time not spent in `article` as parsing. The errors
+    /// of the invalid entries go into `invalid`.
+    fn stream_timed<F>(
+        &self,
+        timings: &Timings,
+        invalid: &mut Vec<ConverterError>,
+        mut article: F,
+    ) -> Result<(), ConverterError>
+    where
+        F: FnMut(SourceArticle),
+    {
+        let start = Instant::now();
+        let mut in_article = Duration::ZERO;
+        let result = self.source.stream_all(
+            &mut |mut source_article| {
+                let article_start = Instant::now();
+                self.fill_names(&mut source_article);
+                article(source_article);
+                in_article += article_start.elapsed();
+            },
+            &mut |error| invalid.push(error),
+        );
+        timings.add(Phase::Parse, start.elapsed().saturating_sub(in_article));
+        result
+    }
+}
+
+/// Whether `destination` is a copy of `output`, `source` itself or what it was processed
+/// into, made after the last change of `source`.
+fn is_up_to_date(source: &Path, output: &Path, destination: &Path) -> bool {
+    match (
+        fs::metadata(source),
+        fs::metadata(output),
+        fs::metadata(destination),
+    ) {
+        (Ok(source), Ok(output), Ok(destination)) => {
+            output.len() == destination.len()
+                && matches!(
+                    (source.modified(), destination.modified()),
+                    (Ok(source), Ok(destination)) if destination >= source
+                )
+        }
+        _ => false,
+    }
+}
+
+/// Builds the article from its source, `clean` turns the HTML into the text. The full
+/// text follows the intro, after the `read_more` line if there is one.
+pub(crate) fn get_article(
+    source: &SourceArticle,
+    read_more: Option<&str>,
+    clean: impl Fn(&str) -> String,
+) -> Article {
+    let mut text = clean(&source.html);
+    if let Some(fulltext) = &source.fulltext {
+        let intro = text.trim_end();
+        let fulltext = clean(fulltext);
+        text = match read_more {
+            Some(read_more) if !intro.is_empty() => {
+                format!("{}\n\n{}\n\n{}", intro, read_more, fulltext)
+            }
+            _ if intro.is_empty() => fulltext,
+            _ => format!("{}\n\n{}", intro, fulltext),
+        };
+    }
+
+    let parts = [Some(source.html.as_str()), source.fulltext.as_deref()];
+    let images: Vec<Image> = parts.into_iter().flatten().flat_map(html::images).collect();
+
+    let decode_all = |values: &[String]| -> Vec<String> {
+        values
+            .iter()
+            .map(|value| html::decode_entities(value))
+            .collect()
+    };
+    Article {
+        original_id: source.id,
+        title: html::decode_entities(&source.title),
+        date: source.created,
+        draft: source.state == State::Unpublished,
+        slug: source.alias.clone(),
+        aliases: Vec::new(),
+        section: None,
+        author: source.author.as_deref().map(html::decode_entities),
+        tags: decode_all(&source.tags),
+        categories: decode_all(&source.categories),
+        text,
+        images,
+        attachments: Vec::new(),
+        image_format: ImageFormat::default(),
+        image_names: None,
+        bundle_thumbnail: false,
+        dir: None,
+        language: None,
+        source_hash: None,
+        converter_hash: None,
+    }
+}
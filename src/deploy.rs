@@ -0,0 +1,45 @@
+//! Publishing the site after a successful conversion, e.g. to a staging environment.
+
+use std::{io, process::Command};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Deploy {
+    /// Build hook URL to `POST` to, like the ones of Netlify. Requested with the `curl`
+    /// command line, which has to be installed.
+    Webhook(String),
+    /// Shell command to run in the working directory, its output is shown as is.
+    Command(String),
+}
+
+impl Deploy {
+    /// Triggers the deployment and waits for it, fails if the hook or command does.
+    pub fn run(&self) -> io::Result<()> {
+        match self {
+            Deploy::Webhook(url) => {
+                let output = Command::new("curl")
+                    .args(["--silent", "--show-error", "--fail", "--request", "POST"])
+                    .arg(url)
+                    .output()?;
+                if output.status.success() {
+                    Ok(())
+                } else {
+                    Err(io::Error::other(format!(
+                        "Deploy hook failed: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    )))
+                }
+            }
+            Deploy::Command(command) => {
+                let status = Command::new("sh").arg("-c").arg(command).status()?;
+                if status.success() {
+                    Ok(())
+                } else {
+                    Err(io::Error::other(format!(
+                        "Deploy command failed with {}",
+                        status
+                    )))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Downloading the images the articles link with an absolute URL, like
+//! `https://old-site.example/images/einsatz.jpg`, instead of a path in the old website.
+//! Needs the `http` feature.
+
+use crate::throttle::Throttle;
+use sha2::{Digest, Sha256};
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// How often a download is tried before the image counts as missing.
+#[cfg(feature = "http")]
+const ATTEMPTS: u32 = 3;
+
+/// How many images are downloaded at once, whatever the number of articles converted in
+/// parallel, so that the old website is not flooded.
+#[cfg(feature = "http")]
+const CONCURRENT_DOWNLOADS: usize = 8;
+
+/// Whether `source`, the `src` of an image, is a URL to download.
+pub fn is_remote(source: &Path) -> bool {
+    source
+        .to_str()
+        .is_some_and(|source| source.starts_with("http://") || source.starts_with("https://"))
+}
+
+/// Downloads the remote images into a cache directory, where later runs find them.
+pub struct Downloads {
+    cache_dir: PathBuf,
+    #[cfg(feature = "http")]
+    fetcher: std::sync::OnceLock<Fetcher>,
+}
+
+/// The runtime and client the downloads of all workers share.
+#[cfg(feature = "http")]
+struct Fetcher {
+    runtime: tokio::runtime::Runtime,
+    client: reqwest::Client,
+    permits: tokio::sync::Semaphore,
+}
+
+impl Downloads {
+    pub fn new(cache_dir: impl Into<PathBuf>) -> Self {
+        Downloads {
+            cache_dir: cache_dir.into(),
+            #[cfg(feature = "http")]
+            fetcher: std::sync::OnceLock::new(),
+        }
+    }
+
+    /// Where the image at `url` is kept, named after the hash of the URL with the extension
+    /// of its path.
+    fn path(&self, url: &str) -> PathBuf {
+        let file_name = url.split(['?', '#']).next().unwrap_or_default();
+        let extension = Path::new(file_name)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .filter(|extension| extension.chars().all(|char| char.is_ascii_alphanumeric()))
+            .map(|extension| format!(".{}", extension.to_lowercase()))
+            .unwrap_or_default();
+        self.cache_dir
+            .join(format!("{:x}{}", Sha256::digest(url), extension))
+    }
+
+    /// Downloads the images at `urls` that are not in the cache already, at the same time,
+    /// and returns the paths of the downloaded files in the order of `urls`. Failed
+    /// attempts are retried after a growing pause. The downloaded images count against
+    /// `throttle`.
+    #[cfg(feature = "http")]
+    pub fn fetch_all(
+        &self,
+        urls: &[String],
+        throttle: Option<&Throttle>,
+    ) -> Vec<io::Result<PathBuf>> {
+        let fetcher = match self.fetcher() {
+            Ok(fetcher) => fetcher,
+            Err(error) => {
+                return urls
+                    .iter()
+                    .map(|_| Err(io::Error::new(error.kind(), error.to_string())))
+                    .collect()
+            }
+        };
+        fetcher.runtime.block_on(futures_util::future::join_all(
+            urls.iter().map(|url| self.fetch(fetcher, url, throttle)),
+        ))
+    }
+
+    /// Only finds the images a build with the `http` feature downloaded.
+    #[cfg(not(feature = "http"))]
+    pub fn fetch_all(
+        &self,
+        urls: &[String],
+        _throttle: Option<&Throttle>,
+    ) -> Vec<io::Result<PathBuf>> {
+        urls.iter()
+            .map(|url| {
+                let path = self.path(url);
+                if path.is_file() {
+                    return Ok(path);
+                }
+                Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "Built without the http feature",
+                ))
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "http")]
+    fn fetcher(&self) -> io::Result<&Fetcher> {
+        use std::time::Duration;
+
+        if let Some(fetcher) = self.fetcher.get() {
+            return Ok(fetcher);
+        }
+        let fetcher = Fetcher {
+            runtime: tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(1)
+                .enable_all()
+                .build()?,
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(60))
+                .build()
+                .map_err(io::Error::other)?,
+            permits: tokio::sync::Semaphore::new(CONCURRENT_DOWNLOADS),
+        };
+        Ok(self.fetcher.get_or_init(|| fetcher))
+    }
+
+    #[cfg(feature = "http")]
+    async fn fetch(
+        &self,
+        fetcher: &Fetcher,
+        url: &str,
+        throttle: Option<&Throttle>,
+    ) -> io::Result<PathBuf> {
+        use std::{fs, io::Write, time::Duration};
+
+        let path = self.path(url);
+        if path.is_file() {
+            return Ok(path);
+        }
+        let mut attempt = 1;
+        let bytes = loop {
+            let response = async {
+                let _permit = fetcher.permits.acquire().await;
+                fetcher
+                    .client
+                    .get(url)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .bytes()
+                    .await
+            };
+            match response.await {
+                Ok(bytes) => {
+                    if let Some(throttle) = throttle {
+                        tokio::time::sleep(throttle.reserve(bytes.len())).await;
+                    }
+                    break bytes;
+                }
+                // Client errors like 404 do not go away by asking again.
+                Err(error)
+                    if error
+                        .status()
+                        .is_some_and(|status| status.is_client_error()) =>
+                {
+                    return Err(io::Error::new(io::ErrorKind::NotFound, error));
+                }
+                Err(error) if attempt == ATTEMPTS => return Err(io::Error::other(error)),
+                Err(error) => {
+                    tracing::debug!("Download of {} failed, retrying: {}", url, error);
+                    tokio::time::sleep(Duration::from_secs(1 << attempt)).await;
+                    attempt += 1;
+                }
+            }
+        };
+        fs::create_dir_all(&self.cache_dir)?;
+        // Written into a file of its own and renamed into place, so that neither an
+        // interrupted download nor another worker fetching the same URL leaves a partial
+        // image behind.
+        let mut partial = tempfile::NamedTempFile::new_in(&self.cache_dir)?;
+        partial.write_all(&bytes)?;
+        partial.persist(&path).map_err(|error| error.error)?;
+        Ok(path)
+    }
+}
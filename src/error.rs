@@ -0,0 +1,146 @@
+//! What can go wrong during a conversion.
+
+use crate::model::Article;
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+/// The article an error is about, as far as it is known.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArticleRef {
+    /// ID of the article in the source CMS.
+    pub id: Option<u64>,
+    pub title: String,
+}
+
+impl ArticleRef {
+    pub fn new(id: Option<u64>, title: impl Into<String>) -> Self {
+        ArticleRef {
+            id,
+            title: title.into(),
+        }
+    }
+}
+
+impl From<&Article> for ArticleRef {
+    fn from(article: &Article) -> Self {
+        ArticleRef::new(article.original_id, article.title.clone())
+    }
+}
+
+impl fmt::Display for ArticleRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.id {
+            Some(id) => write!(f, "\"{}\" (ID {})", self.title, id),
+            None => write!(f, "\"{}\"", self.title),
+        }
+    }
+}
+
+/// Describes the article of an error, if any, to end its message with.
+fn of_article(article: &Option<ArticleRef>) -> String {
+    match article {
+        Some(article) => format!(" of article {}", article),
+        None => String::new(),
+    }
+}
+
+/// Any failure of a conversion, with the article and the file involved.
+#[derive(Debug, thiserror::Error)]
+pub enum ConverterError {
+    /// The input could not be read at all.
+    #[error("Failed to read {}", path.display())]
+    ReadFailed {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    /// The input is not what the source expects, e.g. broken JSON.
+    #[error("Failed to parse {}: {}", path.display(), message)]
+    InputParse { path: PathBuf, message: String },
+
+    /// A field the conversion needs is missing or malformed.
+    #[error("Article {article} in {} has no valid {field}", path.display())]
+    MissingField {
+        article: ArticleRef,
+        path: PathBuf,
+        field: &'static str,
+    },
+
+    #[error("Image {} of article {article} not found", path.display())]
+    ImageNotFound {
+        article: ArticleRef,
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    /// Writing into the output directory failed, outside of an article for the
+    /// directories and indexes of a year.
+    #[error("Failed to write {}{}", path.display(), of_article(article))]
+    WriteFailed {
+        article: Option<ArticleRef>,
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    /// The target could not render the article.
+    #[error("Failed to render {} of article {article}", path.display())]
+    TemplateError {
+        article: ArticleRef,
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+}
+
+impl ConverterError {
+    /// The article the error is about, missing for errors concerning the whole input or year.
+    pub fn article(&self) -> Option<&ArticleRef> {
+        match self {
+            ConverterError::ReadFailed { .. } | ConverterError::InputParse { .. } => None,
+            ConverterError::WriteFailed { article, .. } => article.as_ref(),
+            ConverterError::MissingField { article, .. }
+            | ConverterError::ImageNotFound { article, .. }
+            | ConverterError::TemplateError { article, .. } => Some(article),
+        }
+    }
+
+    /// The file involved.
+    pub fn path(&self) -> &Path {
+        match self {
+            ConverterError::ReadFailed { path, .. }
+            | ConverterError::InputParse { path, .. }
+            | ConverterError::MissingField { path, .. }
+            | ConverterError::ImageNotFound { path, .. }
+            | ConverterError::WriteFailed { path, .. }
+            | ConverterError::TemplateError { path, .. } => path,
+        }
+    }
+
+    /// Wraps a failed copy of the image `source`: a missing source is an
+    /// [`ImageNotFound`](Self::ImageNotFound), anything else a failed write of `destination`.
+    pub(crate) fn copy_failed(
+        article: &Article,
+        source: PathBuf,
+        destination: PathBuf,
+        error: io::Error,
+    ) -> Self {
+        if error.kind() == io::ErrorKind::NotFound && !source.exists() {
+            ConverterError::ImageNotFound {
+                article: article.into(),
+                path: source,
+                source: error,
+            }
+        } else {
+            ConverterError::WriteFailed {
+                article: Some(article.into()),
+                path: destination,
+                source: error,
+            }
+        }
+    }
+}
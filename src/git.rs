@@ -0,0 +1,145 @@
+//! Committing the converted content, so that every run can be reviewed as a diff.
+//! Runs the `git` command line, which has to be installed.
+
+use crate::model::Section;
+use std::{
+    fs, io,
+    path::Path,
+    process::{Command, Output},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommitMode {
+    /// One commit for every year with changes.
+    PerYear,
+    /// One commit for all changes of the run.
+    PerRun,
+}
+
+/// Commits the content of `sections` in `years` in `output_dir`, which becomes a
+/// repository if it is not part of one yet. Years without changes are left out, returns
+/// the subjects of the new commits.
+pub fn commit(
+    output_dir: &Path,
+    sections: &[Section],
+    years: &[u32],
+    mode: CommitMode,
+) -> io::Result<Vec<String>> {
+    if !git(output_dir, &["rev-parse", "--git-dir"])?
+        .status
+        .success()
+    {
+        run(output_dir, &["init", "--quiet"])?;
+    }
+
+    let mut subjects = Vec::new();
+    match mode {
+        CommitMode::PerYear => {
+            for &year in years {
+                let paths = year_paths(output_dir, sections, year);
+                let message = format!(
+                    "Convert missions of {}\n\n{}",
+                    year,
+                    year_summary(output_dir, sections, year)
+                );
+                if let Some(subject) = commit_paths(output_dir, &paths, &message)? {
+                    subjects.push(subject);
+                }
+            }
+        }
+        CommitMode::PerRun => {
+            let paths: Vec<String> = years
+                .iter()
+                .flat_map(|&year| year_paths(output_dir, sections, year))
+                .collect();
+            let mut message = format!("Convert missions {}\n", years_label(years));
+            for &year in years {
+                message.push('\n');
+                message.push_str(&year_summary(output_dir, sections, year));
+            }
+            if let Some(subject) = commit_paths(output_dir, &paths, &message)? {
+                subjects.push(subject);
+            }
+        }
+    }
+    Ok(subjects)
+}
+
+/// The directories of `year` in every section that exist in `output_dir`.
+fn year_paths(output_dir: &Path, sections: &[Section], year: u32) -> Vec<String> {
+    sections
+        .iter()
+        .flat_map(|section| {
+            ["content", "thumbnail"]
+                .iter()
+                .map(move |dir| format!("{}/{}", dir, section.join(year)))
+        })
+        .filter(|path| output_dir.join(path).exists())
+        .collect()
+}
+
+/// What is in the output for `year`, all sections together.
+fn year_summary(output_dir: &Path, sections: &[Section], year: u32) -> String {
+    let articles: Vec<_> = sections
+        .iter()
+        .filter_map(|section| {
+            fs::read_dir(output_dir.join("content").join(section.join(year))).ok()
+        })
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .collect();
+    let images: usize = articles
+        .iter()
+        .filter_map(|article| fs::read_dir(article.path().join("img")).ok())
+        .map(Iterator::count)
+        .sum();
+    format!("{}: {} articles, {} images", year, articles.len(), images)
+}
+
+fn years_label(years: &[u32]) -> String {
+    match (years.iter().min(), years.iter().max()) {
+        (Some(first), Some(last)) if first != last => format!("of {} to {}", first, last),
+        (Some(year), _) => format!("of {}", year),
+        _ => String::new(),
+    }
+}
+
+/// Commits `paths` if anything changed in them.
+fn commit_paths(output_dir: &Path, paths: &[String], message: &str) -> io::Result<Option<String>> {
+    if paths.is_empty() {
+        return Ok(None);
+    }
+    let mut add = vec!["add", "--all", "--"];
+    add.extend(paths.iter().map(String::as_str));
+    run(output_dir, &add)?;
+
+    let mut diff = vec!["diff", "--cached", "--quiet", "--"];
+    diff.extend(paths.iter().map(String::as_str));
+    if git(output_dir, &diff)?.status.success() {
+        return Ok(None);
+    }
+
+    let mut commit = vec!["commit", "--quiet", "--message", message, "--"];
+    commit.extend(paths.iter().map(String::as_str));
+    run(output_dir, &commit)?;
+    Ok(message.lines().next().map(str::to_string))
+}
+
+fn git(dir: &Path, args: &[&str]) -> io::Result<Output> {
+    Command::new("git").arg("-C").arg(dir).args(args).output()
+}
+
+/// Runs git and fails with its error output if it does.
+fn run(dir: &Path, args: &[&str]) -> io::Result<()> {
+    let output = git(dir, args)?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
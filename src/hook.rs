@@ -0,0 +1,28 @@
+//! Site specific tweaks of the articles, run between parsing and output.
+//!
+//! ```no_run
+//! use ff_website_converter::{model::Article, Converter};
+//!
+//! let mut converter = Converter::default();
+//! converter.hooks.push(Box::new(|article: &mut Article| {
+//!     article.title = article.title.replace("Fehlalarm", "Fehlalarmierung");
+//! }));
+//! converter.convert()?;
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+use crate::model::Article;
+
+/// Runs on every article after it has been parsed and cleaned up, before it is written.
+pub trait ArticleHook: Send + Sync {
+    fn apply(&self, article: &mut Article);
+}
+
+impl<F> ArticleHook for F
+where
+    F: Fn(&mut Article) + Send + Sync,
+{
+    fn apply(&self, article: &mut Article) {
+        self(article)
+    }
+}
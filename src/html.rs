@@ -0,0 +1,438 @@
+//! Converting the HTML of the old website into Markdown, see [`to_markdown`], and
+//! finding its images, see [`images`].
+
+use crate::model::Image;
+use scraper::{node::Node, ElementRef, Html, Selector};
+use std::{fmt, path::PathBuf};
+
+/// Elements that only group their content, nothing is lost by keeping just that.
+const TRANSPARENT: [&str; 8] = [
+    "span", "figure", "section", "article", "header", "footer", "main", "aside",
+];
+
+/// The parts of a `<table>`, which is reported as a whole.
+const TABLE_PARTS: [&str; 9] = [
+    "thead", "tbody", "tfoot", "tr", "th", "td", "caption", "colgroup", "col",
+];
+
+/// Converts the HTML fragment `html` into Markdown. Paragraphs, line breaks, bold and
+/// italic text, links and lists are kept, other tags only keep their text. Images are
+/// dropped, the targets add them on their own. Embedded YouTube and Vimeo players
+/// become the shortcodes of Hugo, other `<iframe>`s are dropped. What is lost is told by
+/// [`losses`].
+pub fn to_markdown(html: &str) -> String {
+    let fragment = Html::parse_fragment(html);
+    let mut writer = Writer::default();
+    writer.children(fragment.root_element());
+    let markdown = writer.output.trim();
+    if markdown.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", markdown)
+    }
+}
+
+/// What [`to_markdown`] does not keep of the HTML, for the articles to review by hand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Loss {
+    /// The element is left out with its content, like a `<script>` or the `<iframe>`
+    /// of a map.
+    Dropped(String),
+    /// Only the text of the element is kept, like that of a `<table>` or an `<h2>`.
+    TextOnly(String),
+    /// The `style` attributes, like colors and font sizes, are left out.
+    InlineStyles,
+}
+
+impl fmt::Display for Loss {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Loss::Dropped(name) => write!(f, "dropped <{}>", name),
+            Loss::TextOnly(name) => write!(f, "only the text of <{}>", name),
+            Loss::InlineStyles => write!(f, "dropped inline styles"),
+        }
+    }
+}
+
+/// What converting the HTML fragment `html` with [`to_markdown`] loses, every kind of
+/// element once in the order of the first.
+pub fn losses(html: &str) -> Vec<Loss> {
+    let fragment = Html::parse_fragment(html);
+    let mut writer = Writer::default();
+    writer.children(fragment.root_element());
+    writer.losses
+}
+
+/// The images of the HTML fragment `html` in order, with their alt text and caption.
+/// The caption is the `<figcaption>` of the `<figure>` around an image, otherwise its
+/// `title`.
+pub fn images(html: &str) -> Vec<Image> {
+    let fragment = Html::parse_fragment(html);
+    let selector = Selector::parse("img[src]").expect("The selector is valid");
+    fragment
+        .select(&selector)
+        .map(|img| {
+            let element = img.value();
+            let figcaption = img
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .find(|ancestor| ancestor.value().name() == "figure")
+                .and_then(|figure| {
+                    figure
+                        .descendants()
+                        .filter_map(ElementRef::wrap)
+                        .find(|child| child.value().name() == "figcaption")
+                })
+                .and_then(|figcaption| collapse(&figcaption.text().collect::<String>()));
+            Image {
+                source: image_source(element.attr("src").unwrap_or_default()),
+                alt: element.attr("alt").and_then(collapse),
+                caption: figcaption.or_else(|| element.attr("title").and_then(collapse)),
+                format: None,
+            }
+        })
+        .collect()
+}
+
+/// Number of words of the text of the HTML fragment `html`.
+pub fn word_count(html: &str) -> usize {
+    // Paragraphs are not separated by whitespace, so the text nodes are counted one by one.
+    Html::parse_fragment(html)
+        .root_element()
+        .text()
+        .flat_map(str::split_whitespace)
+        .count()
+}
+
+/// The `src` of an image as a path relative to the old website, see [`local_path`], or
+/// as the URL to download it from.
+fn image_source(src: &str) -> PathBuf {
+    let src = src.trim();
+    if src.starts_with("http://") || src.starts_with("https://") {
+        src.into()
+    } else if let Some(url) = src.strip_prefix("//") {
+        // Protocol relative, the old website is served over HTTPS these days.
+        format!("https://{}", url).into()
+    } else {
+        local_path(src)
+    }
+}
+
+/// The relative link `url` of the old website as a path within it, as editors and
+/// exports from Windows leave them: decoded from `%20` and the like, with backslashes
+/// as separators, without the query, the fragment and a leading slash, with `.` and
+/// `..` resolved but never leaving the old website, and with the umlauts of macOS, an `a` followed by a combining diaeresis, composed.
+pub fn local_path(url: &str) -> PathBuf {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let decoded = compose_umlauts(&decode_percent(path));
+    let mut segments = Vec::new();
+    for segment in decoded.split(['/', '\\']) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.into_iter().collect()
+}
+
+/// `path` with its `%20` and the like decoded.
+fn decode_percent(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut position = 0;
+    while position < bytes.len() {
+        let hex = bytes
+            .get(position + 1..position + 3)
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[position], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                position += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                position += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// `text` with the vowels followed by a combining diaeresis replaced by their umlauts,
+/// the way the files of the old website are named.
+fn compose_umlauts(text: &str) -> String {
+    let mut composed = String::with_capacity(text.len());
+    for character in text.chars() {
+        let umlaut = match (composed.chars().last(), character) {
+            (Some('a'), '\u{308}') => 'ä',
+            (Some('o'), '\u{308}') => 'ö',
+            (Some('u'), '\u{308}') => 'ü',
+            (Some('A'), '\u{308}') => 'Ä',
+            (Some('O'), '\u{308}') => 'Ö',
+            (Some('U'), '\u{308}') => 'Ü',
+            _ => {
+                composed.push(character);
+                continue;
+            }
+        };
+        composed.pop();
+        composed.push(umlaut);
+    }
+    composed
+}
+
+/// `text` with its HTML entities like `&quot;` and `&uuml;` decoded. Joomla keeps the
+/// titles, names and tags escaped, the text and its attributes are decoded by the HTML
+/// parser already.
+pub fn decode_entities(text: &str) -> String {
+    html_escape::decode_html_entities(text).into_owned()
+}
+
+/// `text` with its whitespace collapsed to single spaces, `None` if nothing is left.
+fn collapse(text: &str) -> Option<String> {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    Some(collapsed).filter(|collapsed| !collapsed.is_empty())
+}
+
+/// The shortcode of the video the player at `src` embeds, like
+/// `https://www.youtube-nocookie.com/embed/ID?rel=0` or `//player.vimeo.com/video/ID`.
+fn video_shortcode(src: &str) -> Option<String> {
+    let address = src.trim();
+    let address = ["https:", "http:"]
+        .iter()
+        .find_map(|scheme| address.strip_prefix(scheme))
+        .unwrap_or(address);
+    let address = address.trim_start_matches('/');
+    let address = address.strip_prefix("www.").unwrap_or(address);
+    let (name, rest) = [
+        ("youtube", "youtube.com/embed/"),
+        ("youtube", "youtube-nocookie.com/embed/"),
+        ("vimeo", "player.vimeo.com/video/"),
+    ]
+    .iter()
+    .find_map(|(name, prefix)| Some((name, address.strip_prefix(prefix)?)))?;
+    let id = rest.split(['?', '#', '/', '&']).next().unwrap_or_default();
+    if id.is_empty()
+        || !id
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || "-_".contains(char))
+    {
+        return None;
+    }
+    Some(format!(r#"{{{{< {} id="{}" >}}}}"#, name, id))
+}
+
+#[derive(Default)]
+struct Writer {
+    output: String,
+    /// Prefix of the lines within the current list items.
+    indent: String,
+    /// Marker of a list item, written with the first text of the item.
+    bullet: Option<String>,
+    /// Nothing has been written on the current line yet.
+    line_start: bool,
+    /// The previous line was ended by a `<br>`.
+    after_break: bool,
+    /// What has not been kept, see [`losses`].
+    losses: Vec<Loss>,
+}
+
+impl Writer {
+    fn children(&mut self, element: ElementRef) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => self.text(text),
+                Node::Element(_) => self.element(ElementRef::wrap(child).unwrap()),
+                _ => {}
+            }
+        }
+    }
+
+    fn element(&mut self, element: ElementRef) {
+        let name = element.value().name();
+        if element
+            .value()
+            .attr("style")
+            .is_some_and(|style| !style.trim().is_empty())
+        {
+            self.lose(Loss::InlineStyles);
+        }
+        match name {
+            "p" | "div" => {
+                self.block_break();
+                self.children(element);
+                self.block_break();
+            }
+            "br" => {
+                if self.line_start && self.after_break {
+                    // Two line breaks in a row separate paragraphs.
+                    self.block_break();
+                } else if !self.line_start {
+                    self.hard_break();
+                }
+            }
+            "strong" | "b" => self.wrapped(element, "**", "**"),
+            "em" | "i" => self.wrapped(element, "*", "*"),
+            "a" => match element.value().attr("href") {
+                Some(href) => self.wrapped(element, "[", &format!("]({})", href)),
+                None => self.children(element),
+            },
+            "ul" | "ol" => self.list(element),
+            "iframe" => {
+                if let Some(shortcode) = element.value().attr("src").and_then(video_shortcode) {
+                    self.block_break();
+                    self.start_line();
+                    self.output.push_str(&shortcode);
+                    self.block_break();
+                } else {
+                    self.lose(Loss::Dropped(name.to_string()));
+                }
+            }
+            // The captions go with the images, which the targets add on their own.
+            "img" | "figcaption" => {}
+            "script" | "style" => self.lose(Loss::Dropped(name.to_string())),
+            _ => {
+                if !TRANSPARENT.contains(&name) && !TABLE_PARTS.contains(&name) {
+                    self.lose(Loss::TextOnly(name.to_string()));
+                }
+                self.children(element)
+            }
+        }
+    }
+
+    fn lose(&mut self, loss: Loss) {
+        if !self.losses.contains(&loss) {
+            self.losses.push(loss);
+        }
+    }
+
+    fn list(&mut self, list: ElementRef) {
+        if self.indent.is_empty() {
+            self.block_break();
+        } else {
+            self.line_break();
+        }
+        let ordered = list.value().name() == "ol";
+        let items = list
+            .children()
+            .filter_map(ElementRef::wrap)
+            .filter(|item| item.value().name() == "li");
+        for (number, item) in items.enumerate() {
+            let bullet = if ordered {
+                format!("{}. ", number + 1)
+            } else {
+                "- ".to_string()
+            };
+            self.line_break();
+            let indent = self.indent.len();
+            self.indent.push_str(&" ".repeat(bullet.len()));
+            self.bullet = Some(bullet);
+            self.children(item);
+            self.bullet = None;
+            self.indent.truncate(indent);
+        }
+        if self.indent.is_empty() {
+            self.block_break();
+        } else {
+            self.line_break();
+        }
+    }
+
+    /// Writes the content of `element` between `before` and `after`, with the
+    /// whitespace around it outside, where Markdown expects it.
+    fn wrapped(&mut self, element: ElementRef, before: &str, after: &str) {
+        if element.text().all(|text| text.trim().is_empty()) {
+            self.children(element);
+            return;
+        }
+        self.start_line();
+        let start = self.output.len();
+        self.children(element);
+        let content = self.output.split_off(start);
+        let trimmed = content.trim();
+        if content.starts_with(char::is_whitespace) && !self.output.ends_with(char::is_whitespace) {
+            self.output.push(' ');
+        }
+        self.output.push_str(before);
+        self.output.push_str(trimmed);
+        self.output.push_str(after);
+        if content.ends_with(char::is_whitespace) {
+            self.output.push(' ');
+        }
+    }
+
+    /// Writes `text` with its whitespace collapsed, as a browser shows it.
+    fn text(&mut self, text: &str) {
+        let mut collapsed = String::with_capacity(text.len());
+        for character in text.chars() {
+            if character.is_whitespace() {
+                if !collapsed.ends_with(' ') {
+                    collapsed.push(' ');
+                }
+            } else {
+                // `<` would start raw HTML, which an author wrote as `&lt;`.
+                if "\\*_[]`<".contains(character) {
+                    collapsed.push('\\');
+                }
+                collapsed.push(character);
+            }
+        }
+        let mut text = collapsed.as_str();
+        if self.line_start || self.output.is_empty() {
+            text = text.trim_start();
+            if text.is_empty() {
+                return;
+            }
+            self.start_line();
+        }
+        self.output.push_str(text);
+    }
+
+    /// Writes the indent of the line, or the marker of the list item it begins.
+    fn start_line(&mut self) {
+        if self.line_start || self.output.is_empty() {
+            match self.bullet.take() {
+                Some(bullet) => {
+                    let outer = self.indent.len() - bullet.len();
+                    self.output.push_str(&self.indent[..outer]);
+                    self.output.push_str(&bullet);
+                }
+                None => self.output.push_str(&self.indent),
+            }
+        }
+        self.line_start = false;
+        self.after_break = false;
+    }
+
+    fn line_break(&mut self) {
+        if !self.output.is_empty() && !self.line_start {
+            let end = self.output.trim_end_matches(' ').len();
+            self.output.truncate(end);
+            self.output.push('\n');
+            self.line_start = true;
+        }
+    }
+
+    /// Ends the line with a backslash, which Markdown keeps as a line break. Unlike two
+    /// spaces it survives the one sentence per line of the cleanup.
+    fn hard_break(&mut self) {
+        self.start_line();
+        self.output.push('\\');
+        self.line_break();
+        self.after_break = true;
+    }
+
+    /// Ends the current paragraph, unless a list item has just begun.
+    fn block_break(&mut self) {
+        self.line_break();
+        self.after_break = false;
+        if self.bullet.is_none() && !self.output.is_empty() && !self.output.ends_with("\n\n") {
+            self.output.push('\n');
+        }
+    }
+}
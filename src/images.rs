@@ -0,0 +1,285 @@
+//! Re-encoding the images of the old website instead of copying them as they are, see
+//! [`ImageEncoding`], scaling the thumbnails and removing the EXIF metadata, see
+//! [`strip_exif`]. Needs the `images` feature, except for [`detect_format`] and the
+//! [`ImageCache`].
+
+use crate::model::ImageFormat;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    thread,
+};
+
+/// The quality if none is given, a good compromise for photos.
+pub const QUALITY: u8 = 80;
+
+/// The quality of the JPEGs [`strip_exif`] has to turn upright, close to the original.
+#[cfg(feature = "images")]
+const ROTATED_QUALITY: u8 = 92;
+
+/// JPEG markers of the segments with the EXIF, XMP and IPTC metadata, which hold GPS
+/// positions, camera serial numbers and the like. `APP0` with JFIF and `APP2` with the
+/// color profile are kept.
+#[cfg(feature = "images")]
+const METADATA_MARKERS: [u8; 2] = [0xE1, 0xED];
+
+/// Format and quality every image is re-encoded to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImageEncoding {
+    pub format: ImageFormat,
+    /// From 1 to 100, higher is better and larger.
+    #[serde(default = "default_quality")]
+    pub quality: u8,
+}
+
+fn default_quality() -> u8 {
+    QUALITY
+}
+
+/// Width and height of the thumbnails in pixels, the first image of an article is cropped
+/// to the aspect ratio around its center.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThumbnailSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ImageEncoding {
+    /// Decodes `source`, the content of an image file whatever its format, and writes it
+    /// to `destination`. Returns the size of the written image.
+    #[cfg(feature = "images")]
+    pub fn encode(&self, source: &[u8], destination: &Path) -> io::Result<u64> {
+        let image = open(source)?;
+        self.write(&image, destination)
+    }
+
+    /// Writes `source` scaled and cropped to `size` to `destination`. Returns the size
+    /// of the written image.
+    #[cfg(feature = "images")]
+    pub fn thumbnail(
+        &self,
+        source: &[u8],
+        destination: &Path,
+        size: ThumbnailSize,
+    ) -> io::Result<u64> {
+        let image = open(source)?;
+        let thumbnail = image.resize_to_fill(
+            size.width,
+            size.height,
+            image::imageops::FilterType::Lanczos3,
+        );
+        self.write(&thumbnail, destination)
+    }
+
+    #[cfg(feature = "images")]
+    fn write(&self, image: &image::DynamicImage, destination: &Path) -> io::Result<u64> {
+        use image::codecs::{avif::AvifEncoder, jpeg::JpegEncoder, png::PngEncoder};
+        use std::io::{BufWriter, Write};
+
+        let mut output = BufWriter::new(File::create(destination)?);
+        match self.format {
+            // JPEG has no alpha channel.
+            ImageFormat::Jpeg => image
+                .to_rgb8()
+                .write_with_encoder(JpegEncoder::new_with_quality(&mut output, self.quality))
+                .map_err(io::Error::other)?,
+            // Lossless, the quality does not apply.
+            ImageFormat::Png => image
+                .write_with_encoder(PngEncoder::new(&mut output))
+                .map_err(io::Error::other)?,
+            ImageFormat::Gif => image
+                .to_rgba8()
+                .write_to(&mut output, image::ImageFormat::Gif)
+                .map_err(io::Error::other)?,
+            // The WebP encoder of the image crate is lossless only.
+            ImageFormat::WebP => {
+                let rgba = image.to_rgba8();
+                let webp = webp::Encoder::from_rgba(&rgba, rgba.width(), rgba.height())
+                    .encode(f32::from(self.quality));
+                output.write_all(&webp)?;
+            }
+            ImageFormat::Avif => image
+                .write_with_encoder(AvifEncoder::new_with_speed_quality(
+                    &mut output,
+                    8,
+                    self.quality,
+                ))
+                .map_err(io::Error::other)?,
+        }
+        output.flush()?;
+        Ok(fs::metadata(destination)?.len())
+    }
+
+    #[cfg(not(feature = "images"))]
+    pub fn encode(&self, _source: &[u8], _destination: &Path) -> io::Result<u64> {
+        Err(unsupported())
+    }
+
+    #[cfg(not(feature = "images"))]
+    pub fn thumbnail(
+        &self,
+        _source: &[u8],
+        _destination: &Path,
+        _size: ThumbnailSize,
+    ) -> io::Result<u64> {
+        Err(unsupported())
+    }
+}
+
+/// The processed images of earlier runs, keyed by the hash of the source image and the
+/// settings it was processed with, so that unchanged images are copied instead of being
+/// decoded and encoded again. The cache is only an optimization, failing to read or write
+/// it is not an error.
+#[derive(Debug, Clone)]
+pub struct ImageCache {
+    dir: PathBuf,
+}
+
+impl ImageCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        ImageCache { dir: dir.into() }
+    }
+
+    /// Where the output of processing `source`, the content of the image file, with
+    /// `settings` into `destination` is kept, named with the extension of `destination`.
+    pub fn entry(&self, source: &[u8], settings: &str, destination: &Path) -> PathBuf {
+        let mut hasher = Sha256::new();
+        // Another version may process the images differently.
+        hasher.update(env!("CARGO_PKG_VERSION"));
+        hasher.update([0]);
+        hasher.update(settings);
+        hasher.update([0]);
+        hasher.update(source);
+        let mut entry = self.dir.join(format!("{:x}", hasher.finalize()));
+        if let Some(extension) = destination.extension() {
+            entry.set_extension(extension);
+        }
+        entry
+    }
+
+    /// Copies the cached `entry` to `destination`, or runs `process` to write it there
+    /// and keeps a copy as the entry. Returns the size of the written image.
+    pub fn write(
+        &self,
+        entry: &Path,
+        destination: &Path,
+        process: impl FnOnce() -> io::Result<u64>,
+    ) -> io::Result<u64> {
+        if entry.is_file() {
+            tracing::debug!("Image {} is in the cache", destination.display());
+            return fs::copy(entry, destination);
+        }
+        let written = process()?;
+        let _ = Self::insert(entry, destination);
+        Ok(written)
+    }
+
+    fn insert(entry: &Path, image: &Path) -> io::Result<()> {
+        let cache_dir = entry.parent().expect("Cache entry without directory");
+        fs::create_dir_all(cache_dir)?;
+        // Copied to a temporary file first so a concurrent reader never sees a partial entry.
+        let temporary_path = entry.with_extension(format!("{:?}.tmp", thread::current().id()));
+        fs::copy(image, &temporary_path)?;
+        fs::rename(&temporary_path, entry)
+    }
+}
+
+/// The format of the image file at `path` by its first bytes, whatever its extension.
+/// `None` if it is not one of the [`ImageFormat`]s.
+pub fn detect_format(path: &Path) -> io::Result<Option<ImageFormat>> {
+    let mut header = Vec::with_capacity(12);
+    File::open(path)?.take(12).read_to_end(&mut header)?;
+    Ok(ImageFormat::detect(&header))
+}
+
+/// Decodes `source` and turns it as its EXIF orientation says, the encoders do not
+/// write the orientation along.
+#[cfg(feature = "images")]
+fn open(source: &[u8]) -> io::Result<image::DynamicImage> {
+    use image::{DynamicImage, ImageDecoder, ImageReader};
+    use std::io::Cursor;
+
+    let mut decoder = ImageReader::new(Cursor::new(source))
+        .with_guessed_format()?
+        .into_decoder()
+        .map_err(io::Error::other)?;
+    let orientation = decoder.orientation().map_err(io::Error::other)?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(io::Error::other)?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+/// Writes `source`, the content of an image file, to `destination` without its EXIF, XMP
+/// and IPTC metadata if it is a JPEG, other formats are written as they are. A JPEG that is only upright by its EXIF
+/// orientation is turned and re-encoded, all others keep their pixels untouched.
+/// Returns the size of the written image.
+#[cfg(feature = "images")]
+pub fn strip_exif(source: &[u8], destination: &Path) -> io::Result<u64> {
+    use image::{metadata::Orientation, ImageDecoder, ImageReader};
+    use std::io::Cursor;
+
+    if !source.starts_with(&[0xFF, 0xD8]) {
+        fs::write(destination, source)?;
+        return Ok(source.len() as u64);
+    }
+    let orientation = ImageReader::with_format(Cursor::new(source), image::ImageFormat::Jpeg)
+        .into_decoder()
+        .and_then(|mut decoder| decoder.orientation())
+        .map_err(io::Error::other)?;
+    match without_metadata(source) {
+        Some(stripped) if orientation == Orientation::NoTransforms => {
+            fs::write(destination, &stripped)?;
+            Ok(stripped.len() as u64)
+        }
+        _ => ImageEncoding {
+            format: ImageFormat::Jpeg,
+            quality: ROTATED_QUALITY,
+        }
+        .encode(source, destination),
+    }
+}
+
+/// `jpeg` without the segments of the [`METADATA_MARKERS`], `None` if it is malformed.
+#[cfg(feature = "images")]
+fn without_metadata(jpeg: &[u8]) -> Option<Vec<u8>> {
+    let mut stripped = Vec::with_capacity(jpeg.len());
+    stripped.extend_from_slice(&jpeg[..2]);
+    let mut position = 2;
+    loop {
+        let marker = *jpeg.get(position + 1).filter(|_| jpeg[position] == 0xFF)?;
+        // The entropy coded image data follows the start of scan up to the end, there
+        // is no metadata in it.
+        if marker == 0xDA {
+            stripped.extend_from_slice(&jpeg[position..]);
+            return Some(stripped);
+        }
+        let length = usize::from(u16::from_be_bytes([
+            *jpeg.get(position + 2)?,
+            *jpeg.get(position + 3)?,
+        ]));
+        let end = position + 2 + length;
+        let segment = jpeg.get(position..end)?;
+        if !METADATA_MARKERS.contains(&marker) {
+            stripped.extend_from_slice(segment);
+        }
+        position = end;
+    }
+}
+
+#[cfg(not(feature = "images"))]
+pub fn strip_exif(_source: &[u8], _destination: &Path) -> io::Result<u64> {
+    Err(unsupported())
+}
+
+#[cfg(not(feature = "images"))]
+fn unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Built without the images feature",
+    )
+}
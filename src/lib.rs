@@ -0,0 +1,63 @@
+//! Converts the mission articles of the old Joomla website into Hugo content.
+//!
+//! The conversion streams the [`source`] twice: a first pass numbers the articles of
+//! every year by date, the second one cleans up and writes them one by one through the
+//! [`target`]. Everything is driven by a [`Converter`]:
+//!
+//! ```no_run
+//! use ff_website_converter::{target::Hugo, Converter};
+//!
+//! let converter = Converter::builder()
+//!     .input("missions.json")
+//!     .target(Hugo::default())
+//!     .years(2018..=2021)
+//!     .build()?;
+//! converter.convert()?;
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+mod builder;
+pub mod calendar;
+mod checkpoint;
+#[cfg(feature = "cms")]
+pub mod cms;
+pub mod comments;
+mod config;
+pub mod conflict;
+mod converter;
+pub mod deploy;
+pub mod download;
+pub mod error;
+pub mod git;
+pub mod hook;
+pub mod html;
+pub mod images;
+pub mod links;
+pub mod mapping;
+pub mod model;
+pub mod notification;
+pub mod output;
+pub mod plugins;
+pub mod progress;
+pub mod redirects;
+pub mod render;
+pub mod report;
+pub mod review;
+pub mod search;
+pub mod sentences;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod source;
+pub mod stats;
+pub mod target;
+pub mod throttle;
+pub mod timings;
+pub mod transform;
+pub mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use builder::{ConfigError, ConverterBuilder};
+pub use config::Config;
+pub use converter::{Conversion, Converter, Select};
+pub use error::ConverterError;
@@ -0,0 +1,290 @@
+//! Finding the links of the converted articles that need attention after the
+//! migration, see [`find_links`], and rewriting those between the articles, see
+//! [`rewrite_links`], and to their documents, see [`link_attachments`].
+
+use crate::{
+    html,
+    model::{self, Article, ArticleNumber, Attachment},
+    render::RenderedArticle,
+    source::SourceArticle,
+    target::OutputTarget,
+};
+use regex::Regex;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Extensions of the documents that are copied with the articles linking to them, in
+/// lowercase.
+pub const ATTACHMENT_EXTENSIONS: [&str; 13] = [
+    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "odt", "ods", "odp", "rtf", "txt", "zip",
+];
+
+lazy_static::lazy_static! {
+    static ref HREF_REGEX: Regex = Regex::new("href=\"([^\"]+)\"").unwrap();
+    static ref MARKDOWN_LINK_REGEX: Regex = Regex::new(r"\]\(([^()\s]+)\)").unwrap();
+    // Markdown links and bare URLs, which end before the shortcodes of the images.
+    static ref TEXT_LINK_REGEX: Regex =
+        Regex::new(r#"\]\(([^()\s]+)\)|(https?://[^\s()<>{}"]+)"#).unwrap();
+    static ref SCHEME_REGEX: Regex = Regex::new(r"^[a-zA-Z][a-zA-Z0-9+.-]*:").unwrap();
+    static ref HOST_REGEX: Regex = Regex::new(r"^[a-zA-Z][a-zA-Z0-9+.-]*://([^/:?#]+)").unwrap();
+    // `index.php?...&amp;id=123:alias` and `/123-alias.html` of Joomla.
+    static ref OLD_ARTICLE_REGEX: Regex = Regex::new(r"[?&;]id=(\d+)|/(\d+)-[^/]*$").unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// Into the old website, which goes away with the migration.
+    OldSite,
+    /// Into the new website.
+    NewSite,
+    External,
+}
+
+/// A link of a converted article.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub year: u32,
+    /// Number of the article within its year.
+    pub index: usize,
+    pub url: String,
+    pub kind: LinkKind,
+    /// A Hugo `ref` to the migrated article, if the link points to one.
+    pub rewrite: Option<String>,
+}
+
+impl fmt::Display for Link {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match self.kind {
+            LinkKind::OldSite => "old site",
+            LinkKind::NewSite => "new site",
+            LinkKind::External => "external",
+        };
+        write!(
+            f,
+            "{}-{} {}: {}",
+            self.year,
+            ArticleNumber(self.index),
+            kind,
+            self.url
+        )?;
+        if let Some(rewrite) = &self.rewrite {
+            write!(f, " -> {}", rewrite)?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects the links of `previews`, as returned by [`Converter::preview`](crate::Converter::preview),
+/// from the HTML of the old website, which the cleanup strips, and from the converted text.
+/// Links to `old_hosts` and relative links of the HTML belong to the old website, links to
+/// `new_hosts` and relative links of the text to the new one. Anchors, mail addresses and
+/// the like are left out, every link is listed once per article.
+pub fn find_links(
+    previews: &[(SourceArticle, RenderedArticle)],
+    old_hosts: &[String],
+    new_hosts: &[String],
+) -> Vec<Link> {
+    let migrated: HashMap<u64, &RenderedArticle> = previews
+        .iter()
+        .filter_map(|(_, rendered)| Some((rendered.article.original_id?, rendered)))
+        .collect();
+
+    let mut links = Vec::new();
+    for (source, rendered) in previews {
+        let mut seen = HashSet::new();
+        let html_links = HREF_REGEX
+            .captures_iter(&source.html)
+            .map(|capture| (capture[1].to_string(), LinkKind::OldSite));
+        let text_links = TEXT_LINK_REGEX
+            .captures_iter(&rendered.content)
+            .map(|capture| {
+                let url = match (capture.get(1), capture.get(2)) {
+                    (Some(link), _) => link.as_str(),
+                    // The punctuation after a bare URL ends the sentence.
+                    (_, bare) => bare
+                        .unwrap()
+                        .as_str()
+                        .trim_end_matches(['.', ',', ';', ':', '!', '?']),
+                };
+                (url.to_string(), LinkKind::NewSite)
+            });
+        for (url, relative_kind) in html_links.chain(text_links) {
+            let kind = match HOST_REGEX.captures(&url) {
+                Some(host) if is_host(&host[1], old_hosts) => LinkKind::OldSite,
+                Some(host) if is_host(&host[1], new_hosts) => LinkKind::NewSite,
+                Some(_) if url.starts_with("http") => LinkKind::External,
+                // mailto:, tel: and javascript: have no host and are nothing to check.
+                _ if url.starts_with('#') || SCHEME_REGEX.is_match(&url) => continue,
+                _ => relative_kind,
+            };
+            if !seen.insert(url.clone()) {
+                continue;
+            }
+            let rewrite = match kind {
+                LinkKind::OldSite => {
+                    old_article_id(&url)
+                        .and_then(|id| migrated.get(&id))
+                        .map(|target| {
+                            let path = format!(
+                                "{}/{}",
+                                target.year,
+                                target.article.dir_name(target.index)
+                            );
+                            let path = match &target.article.section {
+                                Some(dir) => format!("{}/{}", dir, path),
+                                None => path,
+                            };
+                            format!("{{{{< ref \"/{}\" >}}}}", path)
+                        })
+                }
+                _ => None,
+            };
+            links.push(Link {
+                year: rendered.year,
+                index: rendered.index,
+                url,
+                kind,
+                rewrite,
+            });
+        }
+    }
+    links
+}
+
+fn is_host(host: &str, hosts: &[String]) -> bool {
+    hosts.iter().any(|known| {
+        host.eq_ignore_ascii_case(known)
+            || host
+                .strip_prefix("www.")
+                .is_some_and(|host| host.eq_ignore_ascii_case(known))
+    })
+}
+
+/// Rewrites the Markdown links of `text` to articles of the old website into the
+/// [links](OutputTarget::article_link) of `target` to the converted articles, which
+/// `articles` has the content paths of by their ID. Relative links and links to `index.php?option=com_content` of any host
+/// count as links of the old website, links to categories and other views are kept.
+/// Returns the rewritten text and the links to articles that were not converted, which
+/// are left as they are.
+pub fn rewrite_links(
+    text: &str,
+    articles: &HashMap<u64, String>,
+    target: &dyn OutputTarget,
+) -> (String, Vec<String>) {
+    let mut unresolved = Vec::new();
+    let text = MARKDOWN_LINK_REGEX.replace_all(text, |capture: &regex::Captures| {
+        let url = &capture[1];
+        let relative = !url.starts_with('#') && !SCHEME_REGEX.is_match(url);
+        let joomla = url.contains("option=com_content");
+        let other_view = url.contains("view=") && !url.contains("view=article");
+        let id = old_article_id(url)
+            .filter(|_| (relative || joomla) && !other_view && attachment_source(url).is_none());
+        match id.map(|id| articles.get(&id)) {
+            Some(Some(path)) => match target.article_link(path) {
+                Some(link) => format!("]({})", link),
+                None => capture[0].to_string(),
+            },
+            Some(None) => {
+                unresolved.push(url.to_string());
+                capture[0].to_string()
+            }
+            None => capture[0].to_string(),
+        }
+    });
+    (text.into_owned(), unresolved)
+}
+
+/// Points the Markdown links of `article` to documents of the old website, like
+/// `images/berichte/Einsatzbericht.pdf`, to its [`attachments`](Article::attachments),
+/// named after the slug of their file name, keeping the fragment like `#page=2`. The
+/// documents are collected once each, those that `exists` does not find are left as they
+/// are and returned.
+pub fn link_attachments(article: &mut Article, exists: impl Fn(&Path) -> bool) -> Vec<PathBuf> {
+    let mut attachments: Vec<Attachment> = Vec::new();
+    let mut missing = Vec::new();
+    let text = MARKDOWN_LINK_REGEX
+        .replace_all(&article.text, |capture: &regex::Captures| {
+            let url = &capture[1];
+            let Some(source) = attachment_source(url) else {
+                return capture[0].to_string();
+            };
+            let fragment = url.find('#').map_or("", |start| &url[start..]);
+            if let Some(attachment) = attachments.iter().find(|known| known.source == source) {
+                return format!("]({}{})", attachment.path(), fragment);
+            }
+            if !exists(&source) {
+                if !missing.contains(&source) {
+                    missing.push(source);
+                }
+                return capture[0].to_string();
+            }
+            let attachment = Attachment {
+                name: attachment_name(&source, &attachments),
+                source,
+            };
+            let link = format!("]({}{})", attachment.path(), fragment);
+            attachments.push(attachment);
+            link
+        })
+        .into_owned();
+    article.text = text;
+    article.attachments = attachments;
+    missing
+}
+
+/// The path in the old website of the document at `url`, `None` if it is no relative
+/// link to a file with one of the [`ATTACHMENT_EXTENSIONS`].
+fn attachment_source(url: &str) -> Option<PathBuf> {
+    if url.starts_with('#') || SCHEME_REGEX.is_match(url) {
+        return None;
+    }
+    let path = html::local_path(url);
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    ATTACHMENT_EXTENSIONS
+        .contains(&extension.as_str())
+        .then_some(path)
+}
+
+/// The slug of the file name of `source` with its extension, with `-2`, `-3` and so on
+/// appended if one of `attachments` has it already.
+fn attachment_name(source: &Path, attachments: &[Attachment]) -> String {
+    let stem = source
+        .file_stem()
+        .map(|stem| model::slugify(&stem.to_string_lossy()))
+        .filter(|stem| !stem.is_empty())
+        .unwrap_or_else(|| "attachment".to_string());
+    let extension = source
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let mut name = format!("{}.{}", stem, extension);
+    let mut count = 1;
+    while attachments.iter().any(|attachment| attachment.name == name) {
+        count += 1;
+        name = format!("{}-{}.{}", stem, count, extension);
+    }
+    name
+}
+
+fn old_article_id(url: &str) -> Option<u64> {
+    let capture = OLD_ARTICLE_REGEX.captures(url)?;
+    capture.get(1).or(capture.get(2))?.as_str().parse().ok()
+}
+
+/// Requests `url` with the `curl` command line, which has to be installed, following
+/// redirects. Returns the final HTTP status, 0 if the server could not be reached.
+pub fn check_external(url: &str) -> io::Result<u16> {
+    let output = Command::new("curl")
+        .args(["--silent", "--head", "--location", "--max-time", "10"])
+        .args(["--output", "/dev/null", "--write-out", "%{http_code}"])
+        .arg(url)
+        .output()?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .unwrap_or(0))
+}
@@ -0,0 +1,47 @@
+//! Where every article of the source went, for scripts that rewrite the internal links
+//! of other pages or set up the redirects of a webserver.
+
+use crate::{render::RenderedArticle, source::SourceArticle, target::OutputTarget};
+use serde::Serialize;
+
+/// Where the mapping goes in the output directory, next to the site.
+pub const MAPPING_FILE: &str = "mapping.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArticleMapping {
+    /// ID of the article in the source CMS.
+    pub id: Option<u64>,
+    pub title: String,
+    /// Path of the article file in the output directory, like `content/2021/0003/index.md`.
+    pub path: String,
+    /// Path of the article in the built site, if the target knows it.
+    pub url: Option<String>,
+    /// URLs of the article on the old website, see
+    /// [`Converter::old_url`](crate::Converter::old_url).
+    pub old_urls: Vec<String>,
+}
+
+/// A mapping for every article of `previews`, as returned by
+/// [`Converter::preview`](crate::Converter::preview).
+pub fn mapping(
+    previews: &[(SourceArticle, RenderedArticle)],
+    target: &dyn OutputTarget,
+) -> Vec<ArticleMapping> {
+    previews
+        .iter()
+        .map(|(source, rendered)| {
+            let article = &rendered.article;
+            let year_dir = match &article.section {
+                Some(dir) => format!("{}/{}", dir, rendered.year),
+                None => rendered.year.to_string(),
+            };
+            ArticleMapping {
+                id: source.id,
+                title: article.title.clone(),
+                path: format!("content/{}/{}", year_dir, rendered.path.display()),
+                url: target.article_url(article, rendered.year, rendered.index),
+                old_urls: article.aliases.clone(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,423 @@
+//! The articles as they flow through the conversion.
+
+use crate::{html, source::SourceArticle};
+use chrono::{Datelike, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{fmt, path::PathBuf};
+
+/// A converted article, ready to be written by an output target.
+///
+/// Articles serialize to JSON and back, which comes in handy to inspect or
+/// reproduce the conversion of a single article.
+#[derive(Debug, Default, Clone, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Article {
+    /// ID of the article in the source CMS.
+    pub original_id: Option<u64>,
+    pub title: String,
+    pub date: NaiveDateTime,
+    /// Written as a draft, as the article is unpublished, see
+    /// [`Converter::drafts`](crate::Converter::drafts).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub draft: bool,
+    /// URL friendly name of the article, if the source has one.
+    pub slug: Option<String>,
+    /// Paths of the article on the old website, which should lead to the new one, see
+    /// [`Converter::old_url`](crate::Converter::old_url).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// Directory of the [`Section`] of the article, `None` for the default layout.
+    #[serde(default)]
+    pub section: Option<String>,
+    pub author: Option<String>,
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// The cleaned up text of the article.
+    pub text: String,
+    pub images: Vec<Image>,
+    /// The documents the text links to, copied next to it, see
+    /// [`Converter::attachments`](crate::Converter::attachments).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+    /// Format the images are written in, which their names end with.
+    #[serde(default)]
+    pub image_format: ImageFormat,
+    /// Pattern of the file names of the images, with the [`IMAGE_NAME_PLACEHOLDERS`], see
+    /// [`ImageName`]. `None` for the [`DEFAULT_IMAGE_NAMES`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_names: Option<String>,
+    /// The thumbnail is written into the article directory, see
+    /// [`bundle_thumbnail_name`](Self::bundle_thumbnail_name), instead of the thumbnail
+    /// tree of the site.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bundle_thumbnail: bool,
+    /// Name of the article directory if it is named after the article, see
+    /// [`ArticleKey::slug`], `None` for its [`ArticleNumber`].
+    #[serde(default)]
+    pub dir: Option<String>,
+    /// Language code of the article like `de`, which its file and images are named with,
+    /// see [`Converter::multilingual`](crate::Converter::multilingual).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// The [`content_hash`](Self::content_hash) of the article, written into the frontmatter
+    /// to tell whether a later run changed it, see
+    /// [`Existing::UpdateChanged`](crate::output::Existing::UpdateChanged).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_hash: Option<String>,
+    /// The [`SourceArticle::hash`](crate::source::SourceArticle::hash) of the article,
+    /// written into the frontmatter to tell whether its source changed since, see
+    /// [`Converter::converter_hash`](crate::Converter::converter_hash).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub converter_hash: Option<String>,
+}
+
+impl Article {
+    /// SHA-256 of everything that goes into the output of the article, except for the
+    /// [`source_hash`](Self::source_hash) itself.
+    pub fn content_hash(&self) -> String {
+        let article = Article {
+            source_hash: None,
+            ..self.clone()
+        };
+        let json = serde_json::to_vec(&article).expect("Articles serialize to JSON");
+        format!("{:x}", Sha256::digest(json))
+    }
+
+    /// Format the `image_index`-th image is written in, its own if it is copied as it is.
+    /// The thumbnail is written in the format of the first image.
+    pub fn format_of_image(&self, image_index: usize) -> ImageFormat {
+        self.images
+            .get(image_index)
+            .and_then(|image| image.format)
+            .unwrap_or(self.image_format)
+    }
+
+    /// Name of the directory of the `index`-th article of its year, which its thumbnail
+    /// and images are named after.
+    pub fn dir_name(&self, index: usize) -> String {
+        match &self.dir {
+            Some(dir) => dir.clone(),
+            None => ArticleNumber(index).to_string(),
+        }
+    }
+
+    /// Name of the thumbnail in the article directory, like `thumbnail.jpg`, with the
+    /// language before the extension like the article file, `thumbnail.de.jpg`.
+    pub fn bundle_thumbnail_name(&self) -> String {
+        let extension = self.format_of_image(0).extension();
+        match &self.language {
+            Some(language) => format!("{}.{}.{}", BUNDLE_THUMBNAIL, language, extension),
+            None => format!("{}.{}", BUNDLE_THUMBNAIL, extension),
+        }
+    }
+
+    /// Name of the thumbnail of the `index`-th article of its year, the name of its
+    /// directory with the language, as translations share the directory.
+    pub fn thumbnail_name(&self, index: usize) -> String {
+        match &self.language {
+            Some(language) => format!("{}-{}", self.dir_name(index), language),
+            None => self.dir_name(index),
+        }
+    }
+}
+
+/// An image of an article.
+#[derive(Debug, Default, Clone, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Image {
+    /// Path of the image, relative to the old website directory.
+    pub source: PathBuf,
+    pub alt: Option<String>,
+    pub caption: Option<String>,
+    /// Format of the image if it is copied as it is and that is not the
+    /// [`image_format`](Article::image_format) of its article, as told by its content.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<ImageFormat>,
+}
+
+impl Image {
+    pub fn new(source: impl Into<PathBuf>) -> Self {
+        Image {
+            source: source.into(),
+            ..Image::default()
+        }
+    }
+}
+
+/// Name of the [bundled](Article::bundle_thumbnail) thumbnails, which is also the name of
+/// their page resource.
+pub const BUNDLE_THUMBNAIL: &str = "thumbnail";
+
+/// Directory of the attachments in the article directory, next to `img`.
+pub const ATTACHMENT_DIR: &str = "files";
+
+/// A document an article links to, like a PDF of the report of a mission.
+#[derive(Debug, Default, Clone, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Attachment {
+    /// Path of the document, relative to the old website directory.
+    pub source: PathBuf,
+    /// File name in the [`ATTACHMENT_DIR`], unique within the article.
+    pub name: String,
+}
+
+impl Attachment {
+    /// Path relative to the article directory, which the text links to.
+    pub fn path(&self) -> String {
+        format!("{}/{}", ATTACHMENT_DIR, self.name)
+    }
+}
+
+/// File format of the images in the output.
+#[derive(
+    Debug, Default, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    /// Also used for the images copied as they are whose format is not known.
+    #[default]
+    Jpeg,
+    Png,
+    Gif,
+    #[serde(rename = "webp")]
+    WebP,
+    Avif,
+}
+
+impl ImageFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Png => "png",
+            ImageFormat::Gif => "gif",
+            ImageFormat::WebP => "webp",
+            ImageFormat::Avif => "avif",
+        }
+    }
+
+    /// The format of an image starting with `header`, by the magic bytes of the formats.
+    /// `None` if it is none of them.
+    pub fn detect(header: &[u8]) -> Option<Self> {
+        if header.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(ImageFormat::Jpeg)
+        } else if header.starts_with(b"\x89PNG\r\n\x1A\n") {
+            Some(ImageFormat::Png)
+        } else if header.starts_with(b"GIF87a") || header.starts_with(b"GIF89a") {
+            Some(ImageFormat::Gif)
+        } else if header.starts_with(b"RIFF") && header.get(8..12) == Some(b"WEBP") {
+            Some(ImageFormat::WebP)
+        } else if matches!(header.get(4..12), Some(b"ftypavif" | b"ftypavis")) {
+            Some(ImageFormat::Avif)
+        } else {
+            None
+        }
+    }
+}
+
+/// What the first pass over the input keeps of an article: enough to number and name it.
+#[derive(Debug, Clone)]
+pub struct ArticleKey {
+    /// Position of the article within the input.
+    pub ordinal: usize,
+    /// ID of the article in the source CMS.
+    pub id: Option<u64>,
+    pub year: u32,
+    pub date: NaiveDateTime,
+    pub catid: Option<u32>,
+    pub title: String,
+    /// Shared by the translations of the article, which get its number, see
+    /// [`Converter::multilingual`](crate::Converter::multilingual).
+    pub association: Option<String>,
+}
+
+impl ArticleKey {
+    pub fn new(article: &SourceArticle) -> Self {
+        ArticleKey {
+            ordinal: article.ordinal,
+            id: article.id,
+            catid: article.catid,
+            year: article.created.year() as u32,
+            date: article.created,
+            // Decoded like the title of the article, so that its slug matches.
+            title: html::decode_entities(&article.title),
+            association: article.association.clone(),
+        }
+    }
+
+    /// The date and the [slugified](slugify) title, e.g. `2019-05-04-brandmeldealarm`.
+    /// Articles of the same day with the same title get the same slug.
+    pub fn slug(&self) -> String {
+        let title = slugify(&self.title);
+        let date = self.date.format("%Y-%m-%d");
+        if title.is_empty() {
+            date.to_string()
+        } else {
+            format!("{}-{}", date, title)
+        }
+    }
+
+    /// The ID of the article, which stays the same whatever is added to the source, or
+    /// its [`slug`](Self::slug) if it has none.
+    pub fn id_dir(&self) -> String {
+        match self.id {
+            Some(id) => id.to_string(),
+            None => self.slug(),
+        }
+    }
+}
+
+/// The language code of the Joomla language tag `tag` as Hugo names the translations
+/// with it, its primary subtag: `de-DE` becomes `de`.
+pub fn language_code(tag: &str) -> String {
+    tag.split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// Makes `text` fit for a path: umlauts and `ß` are transliterated, letters lowercased
+/// and everything in between the letters and digits becomes a single dash.
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for character in text.chars().flat_map(char::to_lowercase) {
+        let transliterated = match character {
+            'ä' => "ae",
+            'ö' => "oe",
+            'ü' => "ue",
+            'ß' => "ss",
+            character if character.is_alphanumeric() => {
+                slug.push(character);
+                continue;
+            }
+            _ => {
+                if !slug.is_empty() && !slug.ends_with('-') {
+                    slug.push('-');
+                }
+                continue;
+            }
+        };
+        slug.push_str(transliterated);
+    }
+    let end = slug.trim_end_matches('-').len();
+    slug.truncate(end);
+    slug
+}
+
+/// A category of the source and where its articles go.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Section {
+    pub catid: u32,
+    /// Directory below `content/` and `thumbnail/` that holds the years of the section,
+    /// `None` to put them there directly.
+    #[serde(default)]
+    pub dir: Option<String>,
+    /// What the articles are called in the titles of the indexes, `None` for the
+    /// [default](crate::target::Strings::section) of the target.
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+impl Section {
+    /// The missions of `catid`, with their years directly below `content/`.
+    pub fn new(catid: u32) -> Self {
+        Section {
+            catid,
+            dir: None,
+            title: None,
+        }
+    }
+
+    /// `path` below the directory of the section, if it has one.
+    pub fn join(&self, path: impl fmt::Display) -> String {
+        match &self.dir {
+            Some(dir) => format!("{}/{}", dir, path),
+            None => path.to_string(),
+        }
+    }
+}
+
+/// Zero padded number of an article within its year, as used in paths.
+#[derive(Debug, Clone, Copy)]
+pub struct ArticleNumber(pub usize);
+
+impl fmt::Display for ArticleNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:0>4}", self.0)
+    }
+}
+
+/// Zero padded number of an image within its article, as used in paths and resource names.
+#[derive(Debug, Clone, Copy)]
+pub struct ImageNumber(pub usize);
+
+impl fmt::Display for ImageNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:0>2}", self.0)
+    }
+}
+
+/// The placeholders of the [`image_names`](Article::image_names) of the articles.
+pub const IMAGE_NAME_PLACEHOLDERS: [&str; 7] = [
+    "{year}",
+    "{article}",
+    "{dir}",
+    "{image}",
+    "{slug}",
+    "{date}",
+    "{ext}",
+];
+
+/// The image names of the articles named by their number.
+pub const DEFAULT_IMAGE_NAMES: &str = "{year}-{article}-{image}.{ext}";
+
+/// The image names of the articles whose directory is named after them, see
+/// [`Article::dir`].
+pub const DEFAULT_DIR_IMAGE_NAMES: &str = "{dir}-{image}.{ext}";
+
+/// File name of an image of an article, unique within the whole site.
+#[derive(Debug, Clone)]
+pub struct ImageName(pub String);
+
+impl ImageName {
+    /// The name of the `image_index`-th image of `article`, the `article_index`-th of `year`,
+    /// after the [`image_names`](Article::image_names) of the article. Translations have
+    /// their language before the image number, they share the directory.
+    pub fn of(article: &Article, year: u32, article_index: usize, image_index: usize) -> Self {
+        let pattern = match (&article.image_names, &article.dir) {
+            (Some(pattern), _) => pattern.as_str(),
+            (None, Some(_)) => DEFAULT_DIR_IMAGE_NAMES,
+            (None, None) => DEFAULT_IMAGE_NAMES,
+        };
+        let image = match &article.language {
+            Some(language) => format!("{}-{}", language, ImageNumber(image_index)),
+            None => ImageNumber(image_index).to_string(),
+        };
+        let values = [
+            year.to_string(),
+            ArticleNumber(article_index).to_string(),
+            article.dir_name(article_index),
+            image,
+            slugify(&article.title),
+            article.date.format("%Y-%m-%d").to_string(),
+            article.format_of_image(image_index).extension().to_string(),
+        ];
+        let mut name = pattern.to_string();
+        for (placeholder, value) in IMAGE_NAME_PLACEHOLDERS.iter().zip(values) {
+            if name.contains(placeholder) {
+                name = name.replace(placeholder, &value);
+            }
+        }
+        ImageName(name)
+    }
+
+    /// Path of the image relative to the article directory.
+    pub fn path(&self) -> String {
+        format!("img/{}", self)
+    }
+}
+
+impl fmt::Display for ImageName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
@@ -0,0 +1,139 @@
+//! Reporting the end of a run, for long conversions nobody watches.
+//! Sent with the `curl` command line, which has to be installed.
+
+use serde::Serialize;
+use std::{
+    io::{self, Write},
+    process::{Command, Stdio},
+};
+
+/// What a notification tells about a run.
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub succeeded: bool,
+    /// Why the run failed.
+    pub error: Option<String>,
+    pub articles: usize,
+    pub images: usize,
+    pub seconds: f64,
+}
+
+impl Summary {
+    fn subject(&self) -> &'static str {
+        if self.succeeded {
+            "Conversion succeeded"
+        } else {
+            "Conversion failed"
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Notification {
+    /// URL the summary is `POST`ed to as JSON.
+    Webhook(String),
+    Email(Email),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Email {
+    /// The mail server, e.g. `smtp://mail.example.org:587` or `smtps://mail.example.org`.
+    pub server: String,
+    /// As `user:password`, if the server needs a login. Handed to curl in a file only the
+    /// user can read, the arguments of a process are visible to everyone on the machine.
+    pub credentials: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+impl Notification {
+    pub fn send(&self, summary: &Summary) -> io::Result<()> {
+        match self {
+            Notification::Webhook(url) => {
+                let json = serde_json::to_vec(summary).map_err(io::Error::other)?;
+                let mut command = Command::new("curl");
+                command
+                    .args(["--request", "POST", "--header"])
+                    .arg("Content-Type: application/json")
+                    .args(["--data-binary", "@-"])
+                    .arg(url);
+                curl(command, &json)
+            }
+            Notification::Email(email) => {
+                let mut command = Command::new("curl");
+                // Unlike `--ssl`, fails instead of going on without TLS.
+                command
+                    .arg("--ssl-reqd")
+                    .args(["--mail-from", &email.from])
+                    .args(["--upload-file", "-"]);
+                for to in &email.to {
+                    command.args(["--mail-rcpt", to]);
+                }
+                // Removed when dropped, after curl is done. The standard input carries the
+                // message.
+                let config = match &email.credentials {
+                    Some(credentials) => Some(credentials_config(credentials)?),
+                    None => None,
+                };
+                if let Some(config) = &config {
+                    command.arg("--config").arg(config.path());
+                }
+                command.arg(&email.server);
+                curl(command, email.message(summary).as_bytes())
+            }
+        }
+    }
+}
+
+impl Email {
+    fn message(&self, summary: &Summary) -> String {
+        let mut body = format!(
+            "{} articles and {} images in {:.0} seconds.\r\n",
+            summary.articles, summary.images, summary.seconds
+        );
+        if let Some(error) = &summary.error {
+            body.push_str(&format!("\r\nError: {}\r\n", error));
+        }
+        format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}",
+            self.from,
+            self.to.join(", "),
+            summary.subject(),
+            body
+        )
+    }
+}
+
+/// A curl config file with the `user:password` of `credentials`, created readable by the
+/// user only.
+fn credentials_config(credentials: &str) -> io::Result<tempfile::NamedTempFile> {
+    let mut config = tempfile::NamedTempFile::new()?;
+    let quoted = credentials.replace('\\', "\\\\").replace('"', "\\\"");
+    writeln!(config, "user = \"{}\"", quoted)?;
+    config.flush()?;
+    Ok(config)
+}
+
+/// Runs curl with `input` on its standard input and fails with its error output if it does.
+fn curl(mut command: Command, input: &[u8]) -> io::Result<()> {
+    let mut child = command
+        .args(["--silent", "--show-error", "--fail"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .expect("The standard input is piped")
+        .write_all(input)?;
+    let output = child.wait_with_output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "Notification failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
@@ -0,0 +1,830 @@
+//! Writing the content tree, the format of the files is up to the
+//! [`OutputTarget`].
+
+use crate::{
+    conflict::{Conflict, Resolution},
+    converter::Context,
+    error::ConverterError,
+    model::{Article, ArticleKey, ArticleNumber, ImageName, Section, ATTACHMENT_DIR},
+    target::{self, OutputTarget},
+    timings::Phase,
+};
+use serde::Deserialize;
+use std::{
+    fmt::Write as _,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+/// Present in a year's content directory while the year is still being converted.
+pub const PARTIAL_MARKER: &str = ".partial";
+
+/// Where the [staged](crate::Converter::staging) years are written in the output
+/// directory, with a `content` and a `thumbnail` directory like the output.
+pub const STAGING_DIR: &str = ".staging";
+
+/// The [checkpoint](crate::Converter::resume) of a conversion in the output directory,
+/// removed once it is complete.
+pub const CHECKPOINT_FILE: &str = ".converter-state.jsonl";
+
+/// The [site configuration](OutputTarget::site_config) in the output directory.
+pub const SITE_CONFIG_FILE: &str = "migration-config.toml";
+
+/// The [CMS configuration](OutputTarget::cms_config) in the output directory. Not
+/// `config.yml`, which Hugo would take for the configuration of the site.
+pub const CMS_CONFIG_FILE: &str = "decap-config.yml";
+
+/// For failures writing the directories and indexes of a year, which belong to no article.
+fn write_failed(path: &Path) -> impl FnOnce(io::Error) -> ConverterError + '_ {
+    |source| ConverterError::WriteFailed {
+        article: None,
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+/// What a conversion does with the output of an earlier run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Existing {
+    /// Skips the years whose content directory is there already.
+    #[default]
+    SkipYears,
+    /// Writes every article, thumbnail and image again. Articles no longer in the source
+    /// are removed.
+    Overwrite,
+    /// Only adds what is missing, the articles, thumbnails and images that are there
+    /// already are left alone.
+    SkipExisting,
+    /// Writes the articles whose [content hash](Article::content_hash) differs from the
+    /// one in their frontmatter, or whose source changed if they have an
+    /// [`Article::converter_hash`] there, and the images changed since they were copied.
+    /// Articles no longer in the source are removed.
+    UpdateChanged,
+    /// Asks the [`Converter::conflicts`](crate::Converter::conflicts) about every article
+    /// whose file is there with another content, and adds the missing ones. The images
+    /// changed since they were copied are copied again.
+    Ask,
+}
+
+/// What a conversion does with the images an article refers to that are not in the
+/// old website.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MissingImages {
+    /// Fails the article.
+    #[default]
+    Fail,
+    /// Leaves the images out of the article.
+    Skip,
+    /// Writes this image instead, a path relative to the old website directory like
+    /// the paths of the images.
+    Placeholder(PathBuf),
+}
+
+impl Article {
+    fn write(
+        &self,
+        article_dir: &Path,
+        year: u32,
+        article_index: usize,
+        context: &Context,
+    ) -> Result<(), ConverterError> {
+        let target = &context.converter.target;
+        let article_path = article_dir.join(target::file_name(&**target, self));
+        let exists = article_path.exists();
+        match context.converter.existing {
+            // A resumed run writes the articles it did not complete again.
+            Existing::SkipYears if exists && !context.converter.resume => {
+                return self.resolve_conflict(&article_path, year, article_index, context);
+            }
+            Existing::Ask if exists => {
+                return self.resolve_conflict(&article_path, year, article_index, context);
+            }
+            Existing::SkipExisting if exists => {
+                tracing::debug!(
+                    "Article {}-{} exists, skipped",
+                    year,
+                    ArticleNumber(article_index)
+                );
+                return Ok(());
+            }
+            Existing::UpdateChanged if exists => {
+                // Hand edits of an article the source did not change survive.
+                let unchanged = fs::read_to_string(&article_path)
+                    .ok()
+                    .is_some_and(|existing| {
+                        match (&self.converter_hash, target.converter_hash(&existing)) {
+                            // Other settings than the earlier run's do not count.
+                            (Some(hash), Some(written)) => *hash == written,
+                            _ => target
+                                .source_hash(&existing)
+                                .is_some_and(|hash| Some(hash) == self.source_hash),
+                        }
+                    });
+                if unchanged {
+                    tracing::debug!(
+                        "Article {}-{} is unchanged, skipped",
+                        year,
+                        ArticleNumber(article_index)
+                    );
+                    return Ok(());
+                }
+            }
+            _ => {}
+        }
+
+        context.timings.time(Phase::Markdown, || {
+            let write_failed = |source| ConverterError::WriteFailed {
+                article: Some(self.into()),
+                path: article_path.clone(),
+                source,
+            };
+            if exists && context.converter.existing == Existing::UpdateChanged {
+                // Leave unchanged articles alone, so that their modification time stays.
+                let mut output = Vec::new();
+                target
+                    .write_article(&mut output, self, year, article_index)
+                    .map_err(write_failed)?;
+                if fs::read(&article_path).is_ok_and(|existing| existing == output) {
+                    return Ok(());
+                }
+                tracing::info!("Article {}-{} updated", year, ArticleNumber(article_index));
+                fs::write(&article_path, output).map_err(write_failed)
+            } else {
+                // The frontmatter consists of many tiny lines, let the buffer group them.
+                File::create(&article_path)
+                    .and_then(|file| {
+                        let mut output = BufWriter::new(file);
+                        target.write_article(&mut output, self, year, article_index)?;
+                        output.flush()
+                    })
+                    .map_err(write_failed)
+            }
+        })
+    }
+
+    /// Writes the article over the file at `article_path` that an earlier run left with
+    /// another content if the [`Converter::conflicts`](crate::Converter::conflicts) say
+    /// so, and keeps it otherwise.
+    fn resolve_conflict(
+        &self,
+        article_path: &Path,
+        year: u32,
+        article_index: usize,
+        context: &Context,
+    ) -> Result<(), ConverterError> {
+        let write_failed = |source| ConverterError::WriteFailed {
+            article: Some(self.into()),
+            path: article_path.to_path_buf(),
+            source,
+        };
+        let mut output = Vec::new();
+        context.timings.time(Phase::Markdown, || {
+            context
+                .converter
+                .target
+                .write_article(&mut output, self, year, article_index)
+                .map_err(write_failed)
+        })?;
+        let existing = fs::read(article_path).map_err(|source| ConverterError::ReadFailed {
+            path: article_path.to_path_buf(),
+            source,
+        })?;
+        if existing == output {
+            tracing::debug!(
+                "Article {}-{} exists, skipped",
+                year,
+                ArticleNumber(article_index)
+            );
+            return Ok(());
+        }
+        let resolution = match &context.converter.conflicts {
+            Some(conflicts) => conflicts.resolve(&Conflict {
+                article: self.into(),
+                path: article_path,
+                existing: &String::from_utf8_lossy(&existing),
+                output: &String::from_utf8_lossy(&output),
+            }),
+            None => Resolution::Keep,
+        };
+        match resolution {
+            Resolution::Keep => {
+                tracing::warn!(
+                    "Article {}-{} exists with another content, kept",
+                    year,
+                    ArticleNumber(article_index)
+                );
+                Ok(())
+            }
+            Resolution::Overwrite => {
+                tracing::info!(
+                    "Article {}-{} overwritten",
+                    year,
+                    ArticleNumber(article_index)
+                );
+                fs::write(article_path, output).map_err(write_failed)
+            }
+        }
+    }
+}
+
+/// Writes the site and CMS configuration of `target` for `sections` and `years`, if it
+/// has them.
+pub fn write_configs(
+    output_dir: &Path,
+    target: &dyn OutputTarget,
+    sections: &[Section],
+    years: &[u32],
+) -> Result<(), ConverterError> {
+    for (file_name, config) in [
+        (SITE_CONFIG_FILE, target.site_config(sections, years)),
+        (CMS_CONFIG_FILE, target.cms_config(sections, years)),
+    ] {
+        if let Some(config) = config {
+            fs::create_dir_all(output_dir).map_err(write_failed(output_dir))?;
+            let config_path = output_dir.join(file_name);
+            fs::write(&config_path, config).map_err(write_failed(&config_path))?;
+        }
+    }
+    Ok(())
+}
+
+/// Where the [archetypes](OutputTarget::archetype) go in the output directory, one per
+/// section named after its directory, `default.md` for the section without one.
+pub const ARCHETYPE_DIR: &str = "archetypes";
+
+/// Writes the section tree of `target` that the articles of `sections` in `years` go
+/// into: the index of every section directory and year, and the archetypes. The files
+/// that are there already are left alone, the site may have edited them. Returns the
+/// written files.
+pub fn write_scaffold(
+    output_dir: &Path,
+    target: &dyn OutputTarget,
+    sections: &[Section],
+    years: &[u32],
+) -> Result<Vec<PathBuf>, ConverterError> {
+    let content_dir = output_dir.join("content");
+    let mut files = Vec::new();
+    for section in sections {
+        if let Some(dir) = &section.dir {
+            files.push((
+                content_dir.join(dir).join("_index.md"),
+                target.section_dir_index(section),
+            ));
+        }
+        for year in years {
+            files.push((
+                content_dir.join(section.join(year)).join("_index.md"),
+                target.section_index(section, *year),
+            ));
+        }
+        let archetype = format!("{}.md", section.dir.as_deref().unwrap_or("default"));
+        files.push((
+            output_dir.join(ARCHETYPE_DIR).join(archetype),
+            target.archetype(),
+        ));
+    }
+
+    let mut written = Vec::new();
+    for (path, output) in files {
+        let Some(output) = output else {
+            continue;
+        };
+        if path.exists() {
+            tracing::debug!("{} exists, skipped", path.display());
+            continue;
+        }
+        fs::create_dir_all(path.parent().unwrap()).map_err(write_failed(&path))?;
+        fs::write(&path, output).map_err(write_failed(&path))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// The articles of one year of a [`Section`], which end up in their own Hugo section.
+pub struct YearArticles {
+    pub section: Section,
+    pub year: u32,
+    /// Sorted by date, the position is the number of the article.
+    pub articles: Vec<ArticleKey>,
+    /// The translations of the articles by the number of the article they translate,
+    /// which they share the directory with.
+    pub translations: Vec<(usize, ArticleKey)>,
+    /// Which articles are written, see [`Converter::select`](crate::Converter::select).
+    selected: Vec<bool>,
+    /// The names of the article directories, `None` for their numbers.
+    dirs: Option<Vec<String>>,
+    /// Articles not written yet, the year is complete once this reaches zero.
+    remaining: AtomicUsize,
+    /// Whether the year is written into the [`STAGING_DIR`] first.
+    staged: bool,
+    /// Whether the thumbnails go into the article directories instead.
+    bundle_thumbnails: bool,
+}
+
+impl YearArticles {
+    /// Collects the articles of `section` in `year`, numbered by date.
+    pub fn new(keys: &[ArticleKey], section: &Section, year: u32) -> Self {
+        // The sort is stable and the keys are in input order, so the article numbering
+        // stays the same between runs.
+        let mut articles: Vec<ArticleKey> = keys
+            .iter()
+            .filter(|key| key.year == year && key.catid == Some(section.catid))
+            .cloned()
+            .collect();
+
+        articles.sort_by_key(|x| x.date);
+        // The first article of an association is numbered, the later ones translate it.
+        let mut numbered: Vec<ArticleKey> = Vec::with_capacity(articles.len());
+        let mut translations = Vec::new();
+        for key in articles {
+            let translated = key.association.as_ref().and_then(|association| {
+                numbered
+                    .iter()
+                    .position(|article| article.association.as_ref() == Some(association))
+            });
+            match translated {
+                Some(article_index) => translations.push((article_index, key)),
+                None => numbered.push(key),
+            }
+        }
+        let articles = numbered;
+        let mut year_articles = YearArticles {
+            section: section.clone(),
+            year,
+            selected: vec![true; articles.len()],
+            dirs: None,
+            remaining: AtomicUsize::new(0),
+            staged: false,
+            bundle_thumbnails: false,
+            articles,
+            translations,
+        };
+        year_articles.count_remaining();
+        year_articles
+    }
+
+    /// Only writes the articles `select` returns `true` for, the others keep their numbers.
+    /// The translations are written with the article they translate.
+    pub fn select(&mut self, select: impl Fn(&ArticleKey) -> bool) {
+        self.selected = self.articles.iter().map(select).collect();
+        self.count_remaining();
+    }
+
+    fn count_remaining(&mut self) {
+        let remaining = self
+            .entries()
+            .filter(|(index, _)| self.is_selected(*index))
+            .count();
+        self.remaining = AtomicUsize::new(remaining);
+    }
+
+    pub fn is_selected(&self, article_index: usize) -> bool {
+        self.selected[article_index]
+    }
+
+    /// The articles with their numbers, then their translations with the same numbers.
+    pub fn entries(&self) -> impl Iterator<Item = (usize, &ArticleKey)> {
+        self.articles.iter().enumerate().chain(
+            self.translations
+                .iter()
+                .map(|(article_index, key)| (*article_index, key)),
+        )
+    }
+
+    /// Names the article directories after the [slugs](ArticleKey::slug) of the articles
+    /// instead of their numbers. Articles with the slug of an earlier one get `-2`, `-3`
+    /// and so on appended, so the names stay the same between runs like the numbers.
+    pub fn name_by_slug(&mut self) {
+        self.name_dirs(ArticleKey::slug);
+    }
+
+    /// Names the article directories after the [IDs](ArticleKey::id_dir) of the articles
+    /// instead of their numbers, which an article added before them in the source does
+    /// not change.
+    pub fn name_by_id(&mut self) {
+        self.name_dirs(ArticleKey::id_dir);
+    }
+
+    fn name_dirs(&mut self, dir_name: impl Fn(&ArticleKey) -> String) {
+        let mut dirs: Vec<String> = Vec::with_capacity(self.articles.len());
+        for key in &self.articles {
+            let name = dir_name(key);
+            let mut dir = name.clone();
+            let mut count = 1;
+            while dirs.contains(&dir) {
+                count += 1;
+                dir = format!("{}-{}", name, count);
+            }
+            dirs.push(dir);
+        }
+        self.dirs = Some(dirs);
+    }
+
+    /// Name of the directory of the article, `None` if it is named by its number.
+    pub fn dir(&self, article_index: usize) -> Option<&str> {
+        self.dirs.as_ref().map(|dirs| dirs[article_index].as_str())
+    }
+
+    /// Path of every selected article in the content directory, like `uebungen/2019/0003`,
+    /// by its ID, for [`Converter::rewrite_links`](crate::Converter::rewrite_links).
+    pub fn article_paths(&self) -> impl Iterator<Item = (u64, String)> + '_ {
+        self.entries()
+            .filter(|(index, _)| self.is_selected(*index))
+            .filter_map(|(index, key)| {
+                let dir = match self.dir(index) {
+                    Some(dir) => dir.to_string(),
+                    None => ArticleNumber(index).to_string(),
+                };
+                Some((key.id?, format!("{}/{}", self.label(), dir)))
+            })
+    }
+
+    /// The year within its section, e.g. `uebungen/2019`, for messages.
+    pub fn label(&self) -> String {
+        self.section.join(self.year)
+    }
+
+    pub fn series_dir(&self, output_dir: &Path) -> PathBuf {
+        output_dir.join("content").join(self.label())
+    }
+
+    pub fn thumbnail_dir(&self, output_dir: &Path) -> PathBuf {
+        output_dir.join("thumbnail").join(self.label())
+    }
+
+    /// Writes the year into the [`STAGING_DIR`] and moves it into place once all of its
+    /// articles have been written, so that a failed run leaves nothing of it in the
+    /// output. Only for years written from scratch, with [`Existing::SkipYears`] and
+    /// [`Existing::Overwrite`].
+    pub fn stage(&mut self) {
+        self.staged = true;
+    }
+
+    /// Writes the thumbnails into the article directories, see
+    /// [`Converter::bundle_thumbnails`](crate::Converter::bundle_thumbnails). The year gets
+    /// no thumbnail directory.
+    pub fn bundle_thumbnails(&mut self) {
+        self.bundle_thumbnails = true;
+    }
+
+    /// Where the articles of the year are written, in the staging directory if it is
+    /// [staged](Self::stage).
+    fn write_series_dir(&self, output_dir: &Path) -> PathBuf {
+        if self.staged {
+            self.series_dir(&output_dir.join(STAGING_DIR))
+        } else {
+            self.series_dir(output_dir)
+        }
+    }
+
+    /// Where the thumbnails of the year are written, see [`write_series_dir`](Self::write_series_dir).
+    fn write_thumbnail_dir(&self, output_dir: &Path) -> PathBuf {
+        if self.staged {
+            self.thumbnail_dir(&output_dir.join(STAGING_DIR))
+        } else {
+            self.thumbnail_dir(output_dir)
+        }
+    }
+
+    /// Creates the directories and the series index of the year.
+    /// Returns `false` if the year has already been converted and must be skipped,
+    /// which it only is with [`Existing::SkipYears`]. A year a previous run left partial
+    /// is only converted again with `resume`.
+    pub fn prepare(
+        &mut self,
+        output_dir: &Path,
+        target: &dyn OutputTarget,
+        existing: Existing,
+        resume: bool,
+    ) -> Result<bool, ConverterError> {
+        let series_dir = self.series_dir(output_dir);
+        let thumbnail_dir = self.thumbnail_dir(output_dir);
+
+        if matches!(existing, Existing::Overwrite | Existing::UpdateChanged) {
+            if !self.staged {
+                self.remove_stale(&series_dir, &thumbnail_dir)?;
+            }
+        } else if existing == Existing::SkipYears && series_dir.exists() {
+            let partial = series_dir.join(PARTIAL_MARKER).exists();
+            if partial && resume {
+                tracing::info!("Year {} was not completed, resuming it", self.label());
+                // Left by a run without staging, it is continued in place.
+                self.staged = false;
+            } else {
+                if partial {
+                    tracing::warn!(
+                        "Year {} was not completed by a previous run, resume or remove it to convert it again",
+                        self.label()
+                    );
+                } else {
+                    tracing::info!("Year {} exists already, skipped", self.label());
+                }
+                return Ok(false);
+            }
+        }
+
+        let series_dir = self.write_series_dir(output_dir);
+        let thumbnail_dir = self.write_thumbnail_dir(output_dir);
+        if self.staged && !resume {
+            // What a failed run left in the staging directory is written again.
+            for dir in [&series_dir, &thumbnail_dir] {
+                if dir.exists() {
+                    fs::remove_dir_all(dir).map_err(write_failed(dir))?;
+                }
+            }
+        }
+        fs::create_dir_all(&series_dir).map_err(write_failed(&series_dir))?;
+        // Marks the year as incomplete until its last article has been written.
+        let partial_marker = series_dir.join(PARTIAL_MARKER);
+        fs::write(&partial_marker, "").map_err(write_failed(&partial_marker))?;
+
+        if !self.bundle_thumbnails {
+            fs::create_dir_all(&thumbnail_dir).map_err(write_failed(&thumbnail_dir))?;
+        }
+
+        // Create the directory tree of all articles up front in one go instead of
+        // interleaving the many small metadata operations with the file copies.
+        for article_index in (0..self.articles.len()).filter(|index| self.is_selected(*index)) {
+            let article_image_dir = self.article_dir(&series_dir, article_index).join("img");
+            fs::create_dir_all(&article_image_dir).map_err(write_failed(&article_image_dir))?;
+        }
+
+        self.write_series_index(&series_dir, target)?;
+        if self.remaining.load(Ordering::Acquire) == 0 {
+            self.finish(output_dir)?;
+        }
+        Ok(true)
+    }
+
+    /// Removes the articles and thumbnails numbered beyond the articles of the year,
+    /// left over from an earlier run with more articles. Named after their slugs, every
+    /// article directory and thumbnail without a current article is removed.
+    fn remove_stale(&self, series_dir: &Path, thumbnail_dir: &Path) -> Result<(), ConverterError> {
+        for dir in [series_dir, thumbnail_dir] {
+            let Ok(entries) = fs::read_dir(dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let stem = path.file_stem().and_then(|stem| stem.to_str());
+                let stale = match (&self.dirs, stem) {
+                    (Some(dirs), Some(stem)) => {
+                        // The content directory also holds the index of the year.
+                        let is_article = dir == thumbnail_dir || path.is_dir();
+                        is_article && !dirs.iter().any(|dir| dir == stem)
+                    }
+                    (None, Some(stem)) => stem
+                        .parse::<usize>()
+                        .is_ok_and(|number| number >= self.articles.len()),
+                    _ => false,
+                };
+                if stale {
+                    tracing::info!("Removing {}, no longer in the source", path.display());
+                    if path.is_dir() {
+                        fs::remove_dir_all(&path)
+                    } else {
+                        fs::remove_file(&path)
+                    }
+                    .map_err(write_failed(&path))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Makes the year available for review as soon as its last article has been written,
+    /// while other years may still be converting.
+    fn finish(&self, output_dir: &Path) -> Result<(), ConverterError> {
+        let partial_marker = self.write_series_dir(output_dir).join(PARTIAL_MARKER);
+        fs::remove_file(&partial_marker).map_err(write_failed(&partial_marker))?;
+        if self.staged {
+            self.move_into_place(output_dir)?;
+        }
+        tracing::info!(
+            "Year {} is complete ({} articles)",
+            self.label(),
+            self.entries()
+                .filter(|(index, _)| self.is_selected(*index))
+                .count()
+        );
+        Ok(())
+    }
+
+    /// Moves the staged year into the output, replacing the year written by an earlier
+    /// run. Each directory is renamed at once, the earlier one is only removed after.
+    fn move_into_place(&self, output_dir: &Path) -> Result<(), ConverterError> {
+        let staging_dir = output_dir.join(STAGING_DIR);
+        let old_dir = staging_dir.join("old");
+        let dirs = [
+            (
+                self.write_series_dir(output_dir),
+                self.series_dir(output_dir),
+                self.series_dir(&old_dir),
+            ),
+            (
+                self.write_thumbnail_dir(output_dir),
+                self.thumbnail_dir(output_dir),
+                self.thumbnail_dir(&old_dir),
+            ),
+        ];
+        for (staged, dir, old) in dirs {
+            // Bundled thumbnails leave no thumbnail directory.
+            if !staged.exists() {
+                continue;
+            }
+            let replaced = dir.exists();
+            if replaced {
+                fs::create_dir_all(old.parent().unwrap()).map_err(write_failed(&old))?;
+                fs::rename(&dir, &old).map_err(write_failed(&dir))?;
+            }
+            fs::create_dir_all(dir.parent().unwrap()).map_err(write_failed(&dir))?;
+            fs::rename(&staged, &dir).map_err(write_failed(&dir))?;
+            if replaced {
+                fs::remove_dir_all(&old).map_err(write_failed(&old))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the article with its images and thumbnail, then finishes the year
+    /// if it was the last one missing.
+    pub(crate) fn convert_article(
+        &self,
+        article: &Article,
+        article_index: usize,
+        context: &Context,
+    ) -> Result<(), ConverterError> {
+        let output_dir = &context.converter.output_dir;
+        self.write_article(
+            &self.write_series_dir(output_dir),
+            article,
+            article_index,
+            context,
+        )?;
+        context.timings.time(Phase::Thumbnails, || {
+            self.copy_thumbnail(output_dir, article, article_index, context)
+        })?;
+        self.article_done(output_dir)
+    }
+
+    /// Counts an article as written, by this run or the resumed one, and finishes the
+    /// year if it was the last one missing.
+    pub(crate) fn article_done(&self, output_dir: &Path) -> Result<(), ConverterError> {
+        if self.remaining.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.finish(output_dir)?;
+        }
+        Ok(())
+    }
+
+    /// Path of the file of `article`, the `article_index`-th of the year, in the content
+    /// directory.
+    pub(crate) fn article_file(
+        &self,
+        article: &Article,
+        article_index: usize,
+        target: &dyn OutputTarget,
+    ) -> PathBuf {
+        self.article_dir(Path::new(&self.label()), article_index)
+            .join(target::file_name(target, article))
+    }
+
+    fn article_dir(&self, series_dir: &Path, article_index: usize) -> PathBuf {
+        match self.dir(article_index) {
+            Some(dir) => series_dir.join(dir),
+            None => series_dir.join(ArticleNumber(article_index).to_string()),
+        }
+    }
+
+    fn write_article(
+        &self,
+        series_dir: &Path,
+        article: &Article,
+        article_index: usize,
+        context: &Context,
+    ) -> Result<(), ConverterError> {
+        let article_dir = self.article_dir(series_dir, article_index);
+        article.write(&article_dir, self.year, article_index, context)?;
+        self.copy_images(&article_dir.join("img"), article_index, article, context)?;
+        copy_attachments(&article_dir.join(ATTACHMENT_DIR), article, context)
+    }
+
+    fn write_series_index(
+        &self,
+        series_dir: &Path,
+        target: &dyn OutputTarget,
+    ) -> Result<(), ConverterError> {
+        if let Some(output) = target.section_index(&self.section, self.year) {
+            let index_path = series_dir.join("_index.md");
+            fs::write(&index_path, output).map_err(write_failed(&index_path))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the thumbnail into the thumbnail directory of the year, or into the article
+    /// directory if the thumbnails are [bundled](Self::bundle_thumbnails).
+    fn copy_thumbnail(
+        &self,
+        output_dir: &Path,
+        article: &Article,
+        article_index: usize,
+        context: &Context,
+    ) -> Result<(), ConverterError> {
+        if let Some(image) = article.images.first() {
+            let source = context.image_path(&image.source);
+            let format = article.format_of_image(0);
+            let destination = if self.bundle_thumbnails {
+                self.article_dir(&self.write_series_dir(output_dir), article_index)
+                    .join(article.bundle_thumbnail_name())
+            } else {
+                self.write_thumbnail_dir(output_dir).join(format!(
+                    "{}.{}",
+                    article.thumbnail_name(article_index),
+                    format.extension()
+                ))
+            };
+            if let Err(error) = context.copy_thumbnail(&source, &destination, format) {
+                return Err(ConverterError::copy_failed(
+                    article,
+                    source,
+                    destination,
+                    error,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn copy_images(
+        &self,
+        article_image_dir: &Path,
+        article_index: usize,
+        article: &Article,
+        context: &Context,
+    ) -> Result<(), ConverterError> {
+        // Reuse the name and path buffers, articles with dozens of images are common.
+        let mut image_name = String::new();
+        let mut image_desination = article_image_dir.to_path_buf();
+        for (image_index, image) in article.images.iter().enumerate() {
+            image_name.clear();
+            write!(
+                image_name,
+                "{}",
+                ImageName::of(article, self.year, article_index, image_index)
+            )
+            .expect("Formatting into a string cannot fail");
+            image_desination.push(&image_name);
+            let image_source = context.image_path(&image.source);
+            let start = Instant::now();
+            if let Err(error) = context.copy_image(&image_source, &image_desination) {
+                return Err(ConverterError::copy_failed(
+                    article,
+                    image_source,
+                    image_desination,
+                    error,
+                ));
+            }
+            context
+                .timings
+                .record_image(image_source.to_string_lossy().into_owned(), start.elapsed());
+            if let Some(progress) = &context.converter.progress {
+                progress.image_done();
+            }
+            image_desination.pop();
+        }
+        Ok(())
+    }
+}
+
+/// Copies the attachments of `article` into `attachment_dir`, which is only created for
+/// the articles that have some.
+fn copy_attachments(
+    attachment_dir: &Path,
+    article: &Article,
+    context: &Context,
+) -> Result<(), ConverterError> {
+    if article.attachments.is_empty() {
+        return Ok(());
+    }
+    fs::create_dir_all(attachment_dir).map_err(|source| ConverterError::WriteFailed {
+        article: Some(article.into()),
+        path: attachment_dir.to_path_buf(),
+        source,
+    })?;
+    for attachment in &article.attachments {
+        let source = context.converter.old_website_dir.join(&attachment.source);
+        let destination = attachment_dir.join(&attachment.name);
+        context
+            .copy_attachment(&source, &destination)
+            .map_err(|source| ConverterError::WriteFailed {
+                article: Some(article.into()),
+                path: destination,
+                source,
+            })?;
+    }
+    Ok(())
+}
@@ -0,0 +1,116 @@
+//! Translating the tags of Joomla content plugins like `{youtube}ID{/youtube}`, which
+//! the HTML keeps as text, into Hugo shortcodes, see [`Plugins`].
+
+use regex::Regex;
+use std::{borrow::Cow, collections::BTreeMap};
+
+/// The placeholders of the templates of [`Plugins`]: what is between the opening and
+/// the closing tag, and what follows the name in the opening tag.
+pub const PLUGIN_PLACEHOLDERS: [&str; 2] = ["{content}", "{params}"];
+
+lazy_static::lazy_static! {
+    // `{name params}`, the closing `{/name}` is looked for after it.
+    static ref TAG_REGEX: Regex = Regex::new(r"\{([a-zA-Z][\w-]*)((?:\s[^{}]*)?)\}").unwrap();
+    static ref ESCAPE_REGEX: Regex = Regex::new(r"\\(.)").unwrap();
+}
+
+/// Templates of the translated plugin tags by the name of the plugin, with the
+/// [`PLUGIN_PLACEHOLDERS`]. An empty template leaves the tag out. The tags of other
+/// plugins are kept, see [`unknown_tags`].
+///
+/// ```
+/// use ff_website_converter::plugins::Plugins;
+///
+/// let plugins = Plugins::default();
+/// assert_eq!(
+///     plugins.translate(r"Video: {youtube}dQw4w9\_WgXcQ{/youtube}"),
+///     r#"Video: {{< youtube id="dQw4w9_WgXcQ" >}}"#
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plugins {
+    templates: BTreeMap<String, String>,
+}
+
+impl Default for Plugins {
+    /// The plugins of the old website: Simple Image Gallery for the `gallery` shortcode
+    /// of Hugo themes like hugo-shortcode-gallery, see
+    /// [`GALLERY`](crate::target::GALLERY), and the videos for the built-in shortcodes.
+    fn default() -> Self {
+        let mut plugins = Plugins {
+            templates: BTreeMap::new(),
+        };
+        plugins.insert("gallery", r#"{{< gallery dir="{content}" >}}"#);
+        plugins.insert("youtube", r#"{{< youtube id="{content}" >}}"#);
+        plugins.insert("vimeo", r#"{{< vimeo id="{content}" >}}"#);
+        plugins
+    }
+}
+
+impl Plugins {
+    /// Translates the tags of the plugin `name`, in any case, after `template`, replacing
+    /// its template if it has one.
+    pub fn insert(&mut self, name: &str, template: &str) -> &mut Self {
+        self.templates
+            .insert(name.to_lowercase(), template.to_string());
+        self
+    }
+
+    /// The templates by the name of their plugin.
+    pub fn templates(&self) -> &BTreeMap<String, String> {
+        &self.templates
+    }
+
+    /// `text`, the Markdown of an article, with the tags of the plugins translated. Only
+    /// allocates if there is one.
+    pub fn translate<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut translated = String::new();
+        let mut position = 0;
+        for capture in TAG_REGEX.captures_iter(text) {
+            let tag = capture.get(0).unwrap();
+            if tag.start() < position || text[..tag.start()].ends_with('{') {
+                continue;
+            }
+            let Some(template) = self.templates.get(&capture[1].to_lowercase()) else {
+                continue;
+            };
+            let closing = format!("{{/{}}}", &capture[1]);
+            let rest = &text[tag.end()..];
+            let (content, end) = match rest.find(&closing) {
+                Some(length) => (&rest[..length], tag.end() + length + closing.len()),
+                None => ("", tag.end()),
+            };
+            translated.push_str(&text[position..tag.start()]);
+            translated.push_str(
+                &template
+                    .replace("{content}", &unescape(content))
+                    .replace("{params}", &unescape(&capture[2])),
+            );
+            position = end;
+        }
+        if position == 0 {
+            return Cow::Borrowed(text);
+        }
+        translated.push_str(&text[position..]);
+        Cow::Owned(translated)
+    }
+}
+
+/// `text` without the backslashes the conversion into Markdown escaped it with, trimmed.
+fn unescape(text: &str) -> String {
+    ESCAPE_REGEX.replace_all(text.trim(), "$1").into_owned()
+}
+
+/// The names of the plugins whose tags are left in `text`, once each, as the
+/// [`Plugins`] do not know them.
+pub fn unknown_tags(text: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for capture in TAG_REGEX.captures_iter(text) {
+        let tag = capture.get(0).unwrap();
+        let name = capture[1].to_lowercase();
+        if !text[..tag.start()].ends_with('{') && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
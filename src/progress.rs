@@ -0,0 +1,20 @@
+//! Following a conversion while it runs, e.g. with progress bars, see [`Progress`].
+
+/// Told about every article and image of [`Converter::convert`](crate::Converter::convert),
+/// from the worker threads. Every method does nothing by default.
+pub trait Progress: Send + Sync {
+    /// The first pass found `articles` articles to write.
+    fn start(&self, _articles: usize) {}
+
+    /// An article with `images` images is being written.
+    fn article_started(&self, _images: usize) {}
+
+    /// An image has been written or was up to date already.
+    fn image_done(&self) {}
+
+    /// An article has been written or has failed.
+    fn article_done(&self) {}
+
+    /// Every article has been written or has failed.
+    fn finish(&self) {}
+}
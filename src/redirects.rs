@@ -0,0 +1,134 @@
+//! Redirects from the URLs of the old Joomla website to the converted articles, for
+//! as long as the old domain still points to the old hosting.
+
+use crate::{render::RenderedArticle, source::SourceArticle, target::OutputTarget};
+use std::fmt::Write;
+
+/// Name of the rules in the output directory.
+pub const HTACCESS_FILE: &str = "redirects.htaccess";
+
+/// Netlify redirects in the static files of the Hugo site, for when the old domain
+/// points to the new website.
+pub const NETLIFY_FILE: &str = "static/_redirects";
+
+/// What a scheme of [`old_url`] may contain.
+pub const OLD_URL_PLACEHOLDERS: [&str; 3] = ["{id}", "{alias}", "{catid}"];
+
+/// The URL of `article` on the old website after `scheme`, like
+/// `/einsaetze/{catid}-einsaetze/{id}-{alias}.html`. `None` if the article has no value
+/// for one of the placeholders of the scheme.
+pub fn old_url(scheme: &str, article: &SourceArticle) -> Option<String> {
+    let values = [
+        article.id.map(|id| id.to_string()),
+        article.alias.clone(),
+        article.catid.map(|catid| catid.to_string()),
+    ];
+    let mut url = scheme.to_string();
+    for (placeholder, value) in OLD_URL_PLACEHOLDERS.iter().zip(values) {
+        if url.contains(placeholder) {
+            url = url.replace(placeholder, &value?);
+        }
+    }
+    Some(url)
+}
+
+/// The first placeholder of `scheme` that is not one of `placeholders`, like the
+/// [`OLD_URL_PLACEHOLDERS`] of [`old_url`].
+pub(crate) fn unknown_placeholder<'a>(scheme: &'a str, placeholders: &[&str]) -> Option<&'a str> {
+    let mut rest = scheme;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .map_or(rest.len(), |end| start + end + 1);
+        let placeholder = &rest[start..end];
+        if !placeholders.contains(&placeholder) {
+            return Some(placeholder);
+        }
+        rest = &rest[end..];
+    }
+    None
+}
+
+/// Apache rewrite rules for the `.htaccess` of the old website, sending the URLs of the
+/// articles of `previews`, as returned by [`Converter::preview`](crate::Converter::preview),
+/// to their pages below `base_url`. Articles without an ID or a URL in `target` are left out.
+pub fn htaccess(
+    previews: &[(SourceArticle, RenderedArticle)],
+    target: &dyn OutputTarget,
+    base_url: &str,
+) -> String {
+    let mut rules = String::from(concat!(
+        "# Redirects of the old Joomla URLs to the migrated articles.\n",
+        "RewriteEngine On\n",
+    ));
+    for (_, rendered) in previews {
+        let article = &rendered.article;
+        let (Some(id), Some(path)) = (
+            article.original_id,
+            target.article_url(article, rendered.year, rendered.index),
+        ) else {
+            continue;
+        };
+        let url = format!(
+            "{}{}",
+            base_url.trim_end_matches('/'),
+            percent_encode(&path)
+        );
+        // `index.php?option=com_content&view=article&id=N:alias` and the search engine
+        // friendly `.../N-alias.html`.
+        write!(
+            rules,
+            concat!(
+                "\n# {title}\n",
+                "RewriteCond %{{QUERY_STRING}} (^|&)view=article(&|$)\n",
+                "RewriteCond %{{QUERY_STRING}} (^|&)id={id}(:|&|$)\n",
+                "RewriteRule ^index\\.php$ {url} [R=301,L,NE,QSD]\n",
+                "RewriteRule (^|/){id}-[^/]*$ {url} [R=301,L,NE]\n",
+            ),
+            title = article.title,
+            id = id,
+            url = url
+        )
+        .expect("Formatting into a string cannot fail");
+    }
+    rules
+}
+
+/// Netlify `_redirects` rules sending the [`aliases`](crate::model::Article::aliases) of
+/// the articles of `previews` to their pages. The query of an alias like
+/// `/index.php?option=com_content&id=42` is matched by its parameters, which Netlify
+/// needs to be all there but in any order.
+pub fn netlify(previews: &[(SourceArticle, RenderedArticle)], target: &dyn OutputTarget) -> String {
+    let mut rules = String::from("# Redirects of the old Joomla URLs to the migrated articles.\n");
+    for (_, rendered) in previews {
+        let article = &rendered.article;
+        let Some(path) = target.article_url(article, rendered.year, rendered.index) else {
+            continue;
+        };
+        for alias in &article.aliases {
+            let (from, query) = alias.split_once('?').unwrap_or((alias, ""));
+            let mut rule = percent_encode(from);
+            for parameter in query.split('&').filter(|parameter| !parameter.is_empty()) {
+                rule.push(' ');
+                rule.push_str(parameter);
+            }
+            writeln!(rules, "{} {} 301", rule, percent_encode(&path))
+                .expect("Formatting into a string cannot fail");
+        }
+    }
+    rules
+}
+
+/// Encodes everything but the unreserved characters and slashes, Apache does not
+/// accept raw umlauts in redirect targets.
+pub(crate) fn percent_encode(path: &str) -> String {
+    let mut encoded = String::new();
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            write!(encoded, "%{:02X}", byte).expect("Formatting into a string cannot fail");
+        }
+    }
+    encoded
+}
@@ -0,0 +1,190 @@
+//! Converting a single article in memory, for tools that want to preview or check
+//! the output of one record without running a whole conversion.
+//!
+//! ```
+//! use ff_website_converter::{render, source::SourceArticle, target::Hugo, transform::Pipeline};
+//!
+//! let source = SourceArticle {
+//!     ordinal: 0,
+//!     id: Some(42),
+//!     title: "Brandmeldealarm".to_string(),
+//!     alias: None,
+//!     created: "2019-05-04T13:37:00".parse()?,
+//!     catid: Some(5),
+//!     html: "<p>Fehlalarm.</p><img src=\"images/einsaetze/bma.jpg\">".to_string(),
+//!     fulltext: None,
+//!     tags: Vec::new(),
+//!     categories: Vec::new(),
+//!     author_id: None,
+//!     author: None,
+//!     state: Default::default(),
+//!     language: None,
+//!     association: None,
+//! };
+//! let rendered = render::convert_article(
+//!     &source,
+//!     &render::RenderOptions {
+//!         target: &Hugo::default(),
+//!         pipeline: &Pipeline::builtin(),
+//!         hooks: &[],
+//!         section: None,
+//!         read_more: Some("<!--more-->"),
+//!         image_format: Default::default(),
+//!         image_names: None,
+//!         bundle_thumbnails: false,
+//!         index: 3,
+//!         dir: None,
+//!         old_url: None,
+//!         multilingual: false,
+//!         attachments: false,
+//!         article_paths: None,
+//!     },
+//! )?;
+//! assert!(rendered.content.contains("title: Brandmeldealarm"));
+//! assert_eq!(rendered.images[0].1.to_str(), Some("img/2019-0003-00.jpg"));
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+use crate::{
+    converter::get_article,
+    error::ConverterError,
+    hook::ArticleHook,
+    links,
+    model::{self, Article, ImageFormat, ImageName},
+    redirects,
+    source::SourceArticle,
+    target::{self, OutputTarget},
+    transform::Pipeline,
+};
+use chrono::Datelike;
+use std::{collections::HashMap, io, path::PathBuf};
+
+/// How [`convert_article`] turns a source article into its output.
+pub struct RenderOptions<'a> {
+    pub target: &'a dyn OutputTarget,
+    pub pipeline: &'a Pipeline,
+    /// Run on the article in order, after the cleanup.
+    pub hooks: &'a [Box<dyn ArticleHook>],
+    /// Directory of the [`Section`](crate::model::Section) of the article, if it has one.
+    pub section: Option<&'a str>,
+    /// Put between the intro and the full text, see [`Converter::read_more`](crate::Converter::read_more).
+    pub read_more: Option<&'a str>,
+    /// Format the images are written in, see [`Converter::images`](crate::Converter::images).
+    pub image_format: ImageFormat,
+    /// Pattern of the image names, see [`Converter::image_names`](crate::Converter::image_names).
+    pub image_names: Option<&'a str>,
+    /// Writes the thumbnail into the article directory, see
+    /// [`Converter::bundle_thumbnails`](crate::Converter::bundle_thumbnails).
+    pub bundle_thumbnails: bool,
+    /// Number of the article within its year, which the paths and image names use.
+    pub index: usize,
+    /// Name of the article directory instead of the number, see
+    /// [`Converter::slug_dirs`](crate::Converter::slug_dirs).
+    pub dir: Option<&'a str>,
+    /// Scheme of the URLs of the old website, see [`Converter::old_url`](crate::Converter::old_url).
+    pub old_url: Option<&'a str>,
+    /// Names the file and images of the article after its language, see
+    /// [`Converter::multilingual`](crate::Converter::multilingual).
+    pub multilingual: bool,
+    /// Points the links to documents to the attachments of the article, see
+    /// [`Converter::attachments`](crate::Converter::attachments).
+    pub attachments: bool,
+    /// Content paths of the other articles by their ID, to rewrite the links to them, see
+    /// [`Converter::rewrite_links`](crate::Converter::rewrite_links).
+    pub article_paths: Option<&'a HashMap<u64, String>>,
+}
+
+/// An article as it would be written by a conversion.
+#[derive(Debug, Clone)]
+pub struct RenderedArticle {
+    pub article: Article,
+    pub year: u32,
+    /// Number of the article within its year.
+    pub index: usize,
+    /// Path of the article file, relative to the year's section directory.
+    pub path: PathBuf,
+    /// Content of the article file.
+    pub content: String,
+    /// Every image as its path in the old website and its path relative to the
+    /// article directory.
+    pub images: Vec<(PathBuf, PathBuf)>,
+    /// Every attachment the same way as the images.
+    pub attachments: Vec<(PathBuf, PathBuf)>,
+}
+
+/// Converts `source` without touching the filesystem, neither the text cache nor
+/// the images are used. Every document linked counts as an attachment.
+pub fn convert_article(
+    source: &SourceArticle,
+    options: &RenderOptions,
+) -> Result<RenderedArticle, ConverterError> {
+    let year = source.created.year() as u32;
+    let mut article = get_article(source, options.read_more, |html| {
+        options.pipeline.apply(html).into_owned()
+    });
+    article.aliases = options
+        .old_url
+        .iter()
+        .filter_map(|scheme| redirects::old_url(scheme, source))
+        .collect();
+    article.section = options.section.map(str::to_string);
+    article.image_format = options.image_format;
+    article.image_names = options.image_names.map(str::to_string);
+    article.bundle_thumbnail = options.bundle_thumbnails;
+    article.dir = options.dir.map(str::to_string);
+    article.language = source
+        .language
+        .as_deref()
+        .filter(|_| options.multilingual)
+        .map(model::language_code);
+    for hook in options.hooks {
+        hook.apply(&mut article);
+    }
+    if options.attachments {
+        links::link_attachments(&mut article, |_| true);
+    }
+    if let Some(article_paths) = options.article_paths {
+        (article.text, _) = links::rewrite_links(&article.text, article_paths, options.target);
+    }
+
+    let path = PathBuf::from(article.dir_name(options.index))
+        .join(target::file_name(options.target, &article));
+    let mut content = Vec::new();
+    let content = options
+        .target
+        .write_article(&mut content, &article, year, options.index)
+        .and_then(|_| {
+            String::from_utf8(content)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+        })
+        .map_err(|source| ConverterError::TemplateError {
+            article: (&article).into(),
+            path: path.clone(),
+            source,
+        })?;
+
+    let images = article
+        .images
+        .iter()
+        .enumerate()
+        .map(|(image_index, image)| {
+            let name = ImageName::of(&article, year, options.index, image_index);
+            (image.source.clone(), PathBuf::from(name.path()))
+        })
+        .collect();
+    let attachments = article
+        .attachments
+        .iter()
+        .map(|attachment| (attachment.source.clone(), PathBuf::from(attachment.path())))
+        .collect();
+
+    Ok(RenderedArticle {
+        path,
+        content,
+        year,
+        index: options.index,
+        images,
+        attachments,
+        article,
+    })
+}
@@ -0,0 +1,305 @@
+//! Summaries of the articles of a conversion, e.g. for dashboards.
+
+use crate::{
+    error::ConverterError,
+    html,
+    model::{Article, ArticleNumber},
+    render::RenderedArticle,
+    source::SourceArticle,
+    Converter,
+};
+use serde::Serialize;
+use std::{fmt, path::PathBuf};
+
+/// File of the [`Report`] of a conversion in the output directory, next to the site.
+pub const REPORT_FILE: &str = "report.json";
+/// The same as a table, one row per article.
+pub const REPORT_CSV_FILE: &str = "report.csv";
+
+/// What became of an article in [`Converter::convert`](crate::Converter::convert).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArticleStatus {
+    Converted,
+    /// Not selected, in a year that exists already, see
+    /// [`Converter::existing`](crate::Converter::existing), or written by the
+    /// [resumed](crate::Converter::resume) run.
+    Skipped,
+    Failed,
+}
+
+impl fmt::Display for ArticleStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ArticleStatus::Converted => "converted",
+            ArticleStatus::Skipped => "skipped",
+            ArticleStatus::Failed => "failed",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArticleReport {
+    /// ID of the article in the source CMS.
+    pub id: Option<u64>,
+    /// Directory of the section, if the article is not directly below `content/`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    pub year: u32,
+    /// Number of the article within its year, as in its path.
+    pub number: String,
+    pub title: String,
+    pub slug: Option<String>,
+    pub images: usize,
+    /// Only known after a conversion, `None` in a preview.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ArticleStatus>,
+    /// Why the article failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Missing images and an empty text.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// What the conversion of the HTML did not keep, like tables and inline styles, see
+    /// [`html::losses`]. The article is worth a review by hand.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub losses: Vec<String>,
+    /// Path of the article file in the content directory, like `2019/0003/index.md`, to
+    /// find the article in messages about it. Not in the report, which has the section,
+    /// year and number.
+    #[serde(skip)]
+    pub file: Option<PathBuf>,
+    /// The errors and warnings of the Hugo build about the article, see
+    /// [`verify::attach_issues`](crate::verify::attach_issues).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub build_issues: Vec<String>,
+}
+
+impl ArticleReport {
+    pub(crate) fn new(article: &Article, year: u32, index: usize) -> Self {
+        ArticleReport {
+            id: article.original_id,
+            section: article.section.clone(),
+            year,
+            number: ArticleNumber(index).to_string(),
+            title: article.title.clone(),
+            slug: article.slug.clone(),
+            images: article.images.len(),
+            status: None,
+            error: None,
+            warnings: Vec::new(),
+            losses: Vec::new(),
+            file: None,
+            build_issues: Vec::new(),
+        }
+    }
+
+    /// An entry of the source that is not a valid article, see
+    /// [`ArticleSource::stream_all`](crate::source::ArticleSource::stream_all). It is in no
+    /// year, which is `0`, and has no number.
+    pub(crate) fn invalid(error: &ConverterError) -> Self {
+        let article = error.article().cloned().unwrap_or_default();
+        ArticleReport {
+            id: article.id,
+            section: None,
+            year: 0,
+            number: String::new(),
+            title: article.title,
+            slug: None,
+            images: 0,
+            status: Some(ArticleStatus::Failed),
+            error: Some(error.to_string()),
+            warnings: Vec::new(),
+            losses: Vec::new(),
+            file: None,
+            build_issues: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Report {
+    pub articles: Vec<ArticleReport>,
+}
+
+impl Report {
+    /// Summarizes the result of [`Converter::preview`](crate::Converter::preview).
+    pub fn from_preview(previews: &[(SourceArticle, RenderedArticle)]) -> Self {
+        let articles = previews
+            .iter()
+            .map(|(source, rendered)| ArticleReport {
+                images: rendered.images.len(),
+                losses: lossy(&source.html),
+                ..ArticleReport::new(&rendered.article, rendered.year, rendered.index)
+            })
+            .collect();
+        Report { articles }
+    }
+
+    /// The articles as CSV with a header row, the warnings and losses separated by `; `.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "id,section,year,number,title,slug,images,status,error,warnings,losses,build_issues\n",
+        );
+        for article in &self.articles {
+            let fields = [
+                article.id.map(|id| id.to_string()).unwrap_or_default(),
+                article.section.clone().unwrap_or_default(),
+                article.year.to_string(),
+                article.number.clone(),
+                article.title.clone(),
+                article.slug.clone().unwrap_or_default(),
+                article.images.to_string(),
+                article
+                    .status
+                    .map(|status| status.to_string())
+                    .unwrap_or_default(),
+                article.error.clone().unwrap_or_default(),
+                article.warnings.join("; "),
+                article.losses.join("; "),
+                article.build_issues.join("; "),
+            ];
+            let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// What converting `html` loses, for [`ArticleReport::losses`].
+pub(crate) fn lossy(html: &str) -> Vec<String> {
+    html::losses(html)
+        .iter()
+        .map(|loss| loss.to_string())
+        .collect()
+}
+
+/// Quotes `field` if it contains a separator, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// What a conversion would write for one year of a section, see [`DryRun`].
+#[derive(Debug, Clone, Serialize)]
+pub struct YearPlan {
+    /// Directory of the section, if the year is not directly below `content/`.
+    pub section: Option<String>,
+    pub year: u32,
+    pub articles: usize,
+    pub images: usize,
+    /// Images that are not in the old website, the conversion fails on them unless told
+    /// otherwise, see [`Converter::missing_images`](crate::Converter::missing_images).
+    pub missing_images: usize,
+    /// The content directory of the year.
+    pub path: PathBuf,
+    /// The directory is there already, the conversion skips the year unless told
+    /// otherwise, see [`Converter::existing`](crate::Converter::existing).
+    pub exists: bool,
+}
+
+/// An image an article refers to that is not in the old website.
+#[derive(Debug, Clone, Serialize)]
+pub struct MissingImage {
+    pub section: Option<String>,
+    pub year: u32,
+    pub number: String,
+    pub title: String,
+    pub path: PathBuf,
+}
+
+impl MissingImage {
+    pub(crate) fn new(
+        section: &Option<String>,
+        year: u32,
+        index: usize,
+        title: &str,
+        path: PathBuf,
+    ) -> Self {
+        MissingImage {
+            section: section.clone(),
+            year,
+            number: ArticleNumber(index).to_string(),
+            title: title.to_string(),
+            path,
+        }
+    }
+}
+
+impl fmt::Display for MissingImage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(dir) = &self.section {
+            write!(f, "{}/", dir)?;
+        }
+        write!(
+            f,
+            "{}-{} {}: {}",
+            self.year,
+            self.number,
+            self.title,
+            self.path.display()
+        )
+    }
+}
+
+/// What a conversion would write, from the result of
+/// [`Converter::preview`](crate::Converter::preview), without writing anything.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DryRun {
+    /// In the order of the preview, years without articles are left out.
+    pub years: Vec<YearPlan>,
+    pub missing_images: Vec<MissingImage>,
+}
+
+impl DryRun {
+    pub fn new(previews: &[(SourceArticle, RenderedArticle)], converter: &Converter) -> Self {
+        let mut dry_run = DryRun::default();
+        for (_, rendered) in previews {
+            let section = &rendered.article.section;
+            let position = dry_run
+                .years
+                .iter()
+                .position(|plan| plan.section == *section && plan.year == rendered.year);
+            let plan = match position {
+                Some(position) => &mut dry_run.years[position],
+                None => {
+                    let path = match section {
+                        Some(dir) => converter.output_dir.join("content").join(dir),
+                        None => converter.output_dir.join("content"),
+                    }
+                    .join(rendered.year.to_string());
+                    dry_run.years.push(YearPlan {
+                        section: section.clone(),
+                        year: rendered.year,
+                        articles: 0,
+                        images: 0,
+                        missing_images: 0,
+                        exists: path.exists(),
+                        path,
+                    });
+                    dry_run.years.last_mut().unwrap()
+                }
+            };
+            plan.articles += 1;
+            plan.images += rendered.images.len();
+            for (source, _) in &rendered.images {
+                let path = converter.old_website_dir.join(source);
+                if !path.is_file() {
+                    plan.missing_images += 1;
+                    dry_run.missing_images.push(MissingImage::new(
+                        section,
+                        rendered.year,
+                        rendered.index,
+                        &rendered.article.title,
+                        path,
+                    ));
+                }
+            }
+        }
+        dry_run
+    }
+}
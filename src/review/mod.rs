@@ -0,0 +1,61 @@
+//! Decisions of a review of the converted articles before they are written,
+//! see the `tui` module for the review itself.
+
+use crate::model::ArticleKey;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+#[cfg(feature = "tui")]
+pub mod tui;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Approved,
+    /// Needs another look before it may be written.
+    Flagged,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Decision {
+    /// Not decided yet if missing.
+    pub status: Option<Status>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+}
+
+/// The decisions of a review by article ID, kept as JSON between sessions.
+/// Articles without an ID cannot be reviewed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Review {
+    pub decisions: BTreeMap<u64, Decision>,
+}
+
+impl Review {
+    /// Reads the review at `path`, a missing file is an empty review.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read(path) {
+            Ok(json) => Ok(serde_json::from_slice(&json)?),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Review::default()),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+
+    pub fn decision(&self, id: u64) -> Option<&Decision> {
+        self.decisions.get(&id)
+    }
+
+    pub fn decision_mut(&mut self, id: u64) -> &mut Decision {
+        self.decisions.entry(id).or_default()
+    }
+
+    pub fn is_approved(&self, key: &ArticleKey) -> bool {
+        key.id
+            .and_then(|id| self.decision(id))
+            .is_some_and(|decision| decision.status == Some(Status::Approved))
+    }
+}
@@ -0,0 +1,176 @@
+//! Terminal UI to review the converted articles next to their source.
+
+use super::{Review, Status};
+use crate::{render::RenderedArticle, source::SourceArticle};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Style},
+    widgets::{Block, Paragraph, Wrap},
+    DefaultTerminal, Frame,
+};
+use std::io;
+
+const HELP: &str = "←/→ article  ↑/↓ scroll  a approve  f flag  u undecide  n note  q quit";
+/// Lines scrolled by page up and down.
+const PAGE: u16 = 20;
+
+/// Shows `articles` one by one, as returned by [`Converter::preview`](crate::Converter::preview),
+/// until the reviewer quits. The decisions are recorded in `review`.
+pub fn run(articles: &[(SourceArticle, RenderedArticle)], review: &mut Review) -> io::Result<()> {
+    if articles.is_empty() {
+        return Ok(());
+    }
+    let mut terminal = ratatui::init();
+    let result = Session {
+        articles,
+        review,
+        current: 0,
+        scroll: 0,
+        note: None,
+    }
+    .run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+struct Session<'a> {
+    articles: &'a [(SourceArticle, RenderedArticle)],
+    review: &'a mut Review,
+    current: usize,
+    scroll: u16,
+    /// The note being edited, if any.
+    note: Option<String>,
+}
+
+impl Session<'_> {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if self.note.is_some() {
+                self.edit_note(key.code);
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Right | KeyCode::Char('l') => self.go_to(self.current + 1),
+                KeyCode::Left | KeyCode::Char('h') => self.go_to(self.current.saturating_sub(1)),
+                KeyCode::Down | KeyCode::Char('j') => self.scroll = self.scroll.saturating_add(1),
+                KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+                KeyCode::PageDown => self.scroll = self.scroll.saturating_add(PAGE),
+                KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(PAGE),
+                KeyCode::Char('a') => self.decide(Some(Status::Approved)),
+                KeyCode::Char('f') => self.decide(Some(Status::Flagged)),
+                KeyCode::Char('u') => self.decide(None),
+                KeyCode::Char('n') => {
+                    if let Some(id) = self.id() {
+                        let note = self
+                            .review
+                            .decision(id)
+                            .map(|decision| decision.note.clone());
+                        self.note = Some(note.unwrap_or_default());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn edit_note(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Enter => {
+                if let (Some(note), Some(id)) = (self.note.take(), self.id()) {
+                    self.review.decision_mut(id).note = note;
+                }
+            }
+            KeyCode::Esc => self.note = None,
+            KeyCode::Backspace => {
+                if let Some(note) = &mut self.note {
+                    note.pop();
+                }
+            }
+            KeyCode::Char(character) => {
+                if let Some(note) = &mut self.note {
+                    note.push(character);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn go_to(&mut self, index: usize) {
+        self.current = index.min(self.articles.len() - 1);
+        self.scroll = 0;
+    }
+
+    /// Records the decision and moves on to the next article.
+    fn decide(&mut self, status: Option<Status>) {
+        if let Some(id) = self.id() {
+            self.review.decision_mut(id).status = status;
+            self.go_to(self.current + 1);
+        }
+    }
+
+    fn id(&self) -> Option<u64> {
+        self.articles[self.current].1.article.original_id
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let (source, rendered) = &self.articles[self.current];
+        let decision = self.id().map(|id| self.review.decision(id));
+        let (status, color) = match decision.map(|decision| decision.and_then(|d| d.status)) {
+            None => ("no ID, cannot be reviewed", Color::DarkGray),
+            Some(None) => ("undecided", Color::Yellow),
+            Some(Some(Status::Approved)) => ("approved", Color::Green),
+            Some(Some(Status::Flagged)) => ("flagged", Color::Red),
+        };
+
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [before, after] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(body);
+
+        let number = rendered.path.parent().unwrap_or(&rendered.path);
+        let title = format!(
+            "{}/{}  {}-{}  {}  [{}]",
+            self.current + 1,
+            self.articles.len(),
+            rendered.year,
+            number.display(),
+            rendered.article.title,
+            status
+        );
+        frame.render_widget(Paragraph::new(title).style(Style::new().fg(color)), header);
+
+        for (text, name, area) in [
+            (source.html.as_str(), "Before (HTML)", before),
+            (rendered.content.as_str(), "After", after),
+        ] {
+            let pane = Paragraph::new(text)
+                .wrap(Wrap { trim: false })
+                .scroll((self.scroll, 0))
+                .block(Block::bordered().title(name));
+            frame.render_widget(pane, area);
+        }
+
+        let footer_text = match (&self.note, decision.flatten()) {
+            (Some(note), _) => format!("Note: {}_  (Enter to save, Esc to cancel)", note),
+            (None, Some(decision)) if !decision.note.is_empty() => {
+                format!("Note: {}  |  {}", decision.note, HELP)
+            }
+            _ => HELP.to_string(),
+        };
+        frame.render_widget(Paragraph::new(footer_text), footer);
+    }
+}
@@ -0,0 +1,41 @@
+//! An index for full-text search on the static site, as the JSON array of documents
+//! Fuse.js and Lunr are built from.
+
+use crate::{render::RenderedArticle, source::SourceArticle, target::OutputTarget};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+/// Where the index goes in the output directory, Hugo publishes it as `/search-index.json`.
+pub const SEARCH_INDEX_FILE: &str = "static/search-index.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchDocument {
+    pub title: String,
+    pub date: NaiveDateTime,
+    pub tags: Vec<String>,
+    /// The cleaned up text, without frontmatter and shortcodes.
+    pub text: String,
+    /// Path of the article in the site, if the target knows it.
+    pub url: Option<String>,
+}
+
+/// A document for every article of `previews`, as returned by
+/// [`Converter::preview`](crate::Converter::preview).
+pub fn index(
+    previews: &[(SourceArticle, RenderedArticle)],
+    target: &dyn OutputTarget,
+) -> Vec<SearchDocument> {
+    previews
+        .iter()
+        .map(|(_, rendered)| {
+            let article = &rendered.article;
+            SearchDocument {
+                title: article.title.clone(),
+                date: article.date,
+                tags: article.tags.clone(),
+                text: article.text.clone(),
+                url: target.article_url(article, rendered.year, rendered.index),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,123 @@
+//! Putting every sentence of the Markdown text on its own line, which keeps the diffs
+//! of later edits to an article small.
+
+/// Words that are written with a dot without ending the sentence, lowercased. Single
+/// letters, numbers and words with a dot inside like `z.B.` are not listed, they never
+/// end one.
+const ABBREVIATIONS: &[&str] = &[
+    "abs", "abt", "allg", "bspw", "bzgl", "bzw", "ca", "dgl", "di", "do", "dr", "ehem", "etc",
+    "evtl", "fa", "fr", "gem", "ggf", "hr", "inkl", "jun", "kfz", "lkw", "lt", "max", "mi", "min",
+    "mind", "mio", "mo", "nr", "pkw", "rd", "sa", "sen", "sog", "std", "str", "tel", "usw", "vgl",
+    "zzgl", "jan", "feb", "mär", "apr", "aug", "sep", "sept", "okt", "nov", "dez",
+];
+
+/// What may follow the punctuation at the end of a sentence before the space.
+const CLOSING: &[char] = &[')', '"', '\'', '»', '«', '“', '”', '*', '_'];
+
+/// Breaks the lines of `text` after every sentence. Lines of tables, headings and code
+/// blocks are kept as they are, the sentences of list items and quotes stay within them.
+pub fn one_per_line(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut in_code = false;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+        }
+        if in_code || trimmed.starts_with("```") || trimmed.starts_with(['|', '#']) {
+            output.push_str(line);
+        } else {
+            split_line(line, &mut output);
+        }
+    }
+    output
+}
+
+/// Writes `line` with a line break after every sentence into `output`.
+fn split_line(line: &str, output: &mut String) {
+    let chars: Vec<(usize, char)> = line.char_indices().collect();
+    let continuation = continuation(line);
+    let mut start = 0;
+    let mut index = 0;
+    while index < chars.len() {
+        if !matches!(chars[index].1, '.' | '!' | '?') {
+            index += 1;
+            continue;
+        }
+        let mut end = index + 1;
+        while end < chars.len() && matches!(chars[end].1, '.' | '!' | '?') {
+            end += 1;
+        }
+        while end < chars.len() && CLOSING.contains(&chars[end].1) {
+            end += 1;
+        }
+        let mut next = end;
+        while next < chars.len() && chars[next].1 == ' ' {
+            next += 1;
+        }
+        let punctuation: String = chars[index..end].iter().map(|(_, char)| char).collect();
+        let is_end = next > end
+            && next < chars.len()
+            && !matches!(chars[next].1, '\n' | '\\')
+            && ends_sentence(&line[..chars[index].0], &punctuation, chars[next].1);
+        if is_end {
+            output.push_str(&line[start..chars[end].0]);
+            output.push('\n');
+            output.push_str(&continuation);
+            start = chars[next].0;
+        }
+        index = next.max(index + 1);
+    }
+    output.push_str(&line[start..]);
+}
+
+/// Whether `punctuation` after `before` ends a sentence that `next` starts the next one of.
+fn ends_sentence(before: &str, punctuation: &str, next: char) -> bool {
+    if next.is_lowercase() {
+        return false;
+    }
+    if punctuation.starts_with("..") {
+        return false;
+    }
+    if !punctuation.starts_with('.') {
+        return true;
+    }
+    let word = before
+        .rsplit(char::is_whitespace)
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches(['(', '"', '\'', '„', '»', '*', '_']);
+    let Some(last) = word.chars().last() else {
+        return true;
+    };
+    // Ordinals and dates like `3. Mai` and `11.03. Wir`, initials like `A. Müller`.
+    if last.is_ascii_digit() || word.chars().count() == 1 {
+        return false;
+    }
+    if word.contains('.') && word.chars().all(|char| char.is_alphabetic() || char == '.') {
+        return false;
+    }
+    !ABBREVIATIONS.contains(&word.to_lowercase().as_str())
+}
+
+/// What the lines after the first sentence of `line` start with, so that they stay in
+/// its list item or quote.
+fn continuation(line: &str) -> String {
+    let rest = line.trim_start_matches(' ');
+    let indent = &line[..line.len() - rest.len()];
+    if rest.starts_with("> ") {
+        return format!("{}> ", indent);
+    }
+    let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let marker = if ["- ", "* ", "+ "]
+        .iter()
+        .any(|bullet| rest.starts_with(bullet))
+    {
+        2
+    } else if digits > 0 && rest[digits..].starts_with(". ") {
+        digits + 2
+    } else {
+        0
+    };
+    format!("{}{}", indent, " ".repeat(marker))
+}
@@ -0,0 +1,141 @@
+//! The conversion as an HTTP API, see [`serve`].
+
+use crate::{
+    model::ArticleKey,
+    render::{self, RenderOptions, RenderedArticle},
+    report::Report,
+    source::JoomlaRow,
+    Converter,
+};
+use std::{
+    fs,
+    io::{self, Cursor, Read, Write},
+    path::Path,
+};
+use tiny_http::{Header, Method, Request, Response, Server};
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+type HttpResponse = Response<Cursor<Vec<u8>>>;
+
+/// Answers requests on `address` until the process is stopped:
+///
+/// - `POST /convert?index=N` takes one entry of the `data` array of a Joomla export
+///   as JSON and returns a zip with the article directory, numbered `N` or 0, as the
+///   target of `converter` writes it, including the images found in the old website.
+/// - `GET /report` returns a JSON [`Report`] of all articles of the source of `converter`.
+///
+/// Requests are answered one after another.
+pub fn serve(converter: &Converter, address: &str) -> io::Result<()> {
+    let server = Server::http(address).map_err(io::Error::other)?;
+    tracing::info!("Listening on http://{}", address);
+    for mut request in server.incoming_requests() {
+        let response = handle(converter, &mut request);
+        if let Err(error) = request.respond(response) {
+            tracing::warn!("Failed to respond: {}", error);
+        }
+    }
+    Ok(())
+}
+
+fn handle(converter: &Converter, request: &mut Request) -> HttpResponse {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    match (request.method(), path) {
+        (Method::Post, "/convert") => {
+            let index = query
+                .split('&')
+                .find_map(|parameter| parameter.strip_prefix("index="))
+                .map(str::parse::<usize>);
+            match index.unwrap_or(Ok(0)) {
+                Ok(index) => convert(converter, request.as_reader(), index),
+                Err(_) => error(400, "The index must be a number"),
+            }
+        }
+        (Method::Get, "/report") => match converter.preview() {
+            Ok(previews) => match serde_json::to_vec(&Report::from_preview(&previews)) {
+                Ok(json) => with_content_type(Response::from_data(json), "application/json"),
+                Err(json_error) => error(500, json_error.to_string()),
+            },
+            Err(converter_error) => error(500, converter_error.to_string()),
+        },
+        _ => error(404, format!("No {} {}", request.method(), path)),
+    }
+}
+
+fn convert(converter: &Converter, body: &mut dyn Read, index: usize) -> HttpResponse {
+    let row: JoomlaRow = match serde_json::from_reader(body) {
+        Ok(row) => row,
+        Err(json_error) => return error(400, json_error.to_string()),
+    };
+    let mut source = match row.into_source_article(0, Path::new("request")) {
+        Ok(Some(source)) => source,
+        Ok(None) => return error(400, "The entry has no creation date or category"),
+        Err(converter_error) => return error(400, converter_error.to_string()),
+    };
+    converter.fill_names(&mut source);
+    // Without the other articles of the year, a slug taken twice keeps no suffix.
+    let key = ArticleKey::new(&source);
+    let dir = if converter.id_dirs {
+        Some(key.id_dir())
+    } else {
+        converter.slug_dirs.then(|| key.slug())
+    };
+    let options = RenderOptions {
+        target: &*converter.target,
+        pipeline: &converter.pipeline,
+        hooks: &converter.hooks,
+        section: converter
+            .sections
+            .iter()
+            .find(|section| Some(section.catid) == source.catid)
+            .and_then(|section| section.dir.as_deref()),
+        read_more: converter.read_more.as_deref(),
+        image_format: converter.image_format(),
+        image_names: converter.image_names.as_deref(),
+        bundle_thumbnails: converter.bundle_thumbnails,
+        index,
+        dir: dir.as_deref(),
+        old_url: converter.old_url.as_deref(),
+        multilingual: converter.multilingual,
+        attachments: converter.attachments,
+        // A single article has no others to link to.
+        article_paths: None,
+    };
+    let rendered = match render::convert_article(&source, &options) {
+        Ok(rendered) => rendered,
+        Err(converter_error) => return error(422, converter_error.to_string()),
+    };
+    match bundle(&rendered, &converter.old_website_dir) {
+        Ok(zip) => with_content_type(Response::from_data(zip), "application/zip"),
+        Err(zip_error) => error(500, zip_error.to_string()),
+    }
+}
+
+/// Zips the article directory, images and attachments missing in the old website are
+/// left out.
+fn bundle(rendered: &RenderedArticle, old_website_dir: &Path) -> io::Result<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default();
+    let article_dir = rendered.path.parent().unwrap_or(Path::new(""));
+
+    zip.start_file(rendered.path.to_string_lossy(), options)?;
+    zip.write_all(rendered.content.as_bytes())?;
+    for (source, destination) in rendered.images.iter().chain(&rendered.attachments) {
+        if let Ok(image) = fs::read(old_website_dir.join(source)) {
+            zip.start_file(article_dir.join(destination).to_string_lossy(), options)?;
+            zip.write_all(&image)?;
+        }
+    }
+    Ok(zip.finish()?.into_inner())
+}
+
+fn error(status: u16, message: impl Into<String>) -> HttpResponse {
+    with_content_type(Response::from_string(message), "text/plain; charset=utf-8")
+        .with_status_code(status)
+}
+
+fn with_content_type(response: HttpResponse, content_type: &str) -> HttpResponse {
+    let header =
+        Header::from_bytes("Content-Type", content_type).expect("Content types are valid headers");
+    response.with_header(header)
+}
@@ -0,0 +1,302 @@
+//! The JSON export of the Joomla `#__content` table, as written by phpMyAdmin.
+
+use super::{ArticleSource, SourceArticle, State};
+use crate::error::{ArticleRef, ConverterError};
+use chrono::NaiveDateTime;
+use memmap2::Mmap;
+use serde::{
+    de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    Deserialize,
+};
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufReader},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Format of the `created` timestamps in the export.
+pub const DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// One row of the `data` array. phpMyAdmin writes every column as a string, the
+/// typed values are parsed from them in [`JoomlaRow::into_source_article`] so that
+/// errors can name the article and field.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct JoomlaRow {
+    #[serde(deserialize_with = "string_or_number")]
+    pub id: Option<String>,
+    pub title: Option<String>,
+    pub alias: Option<String>,
+    pub created: Option<String>,
+    /// When the article went online, the date of the articles with a zero `created`.
+    pub publish_up: Option<String>,
+    #[serde(deserialize_with = "string_or_number")]
+    pub catid: Option<String>,
+    pub introtext: Option<String>,
+    pub fulltext: Option<String>,
+    /// The meta keywords, separated by commas.
+    pub metakey: Option<String>,
+    #[serde(deserialize_with = "string_or_number")]
+    pub created_by: Option<String>,
+    pub created_by_alias: Option<String>,
+    /// `1` for published, `0` for unpublished, `2` for archived and `-2` for trashed.
+    /// Exports without it are taken as published.
+    #[serde(deserialize_with = "string_or_number")]
+    pub state: Option<String>,
+    /// Language tag like `de-DE`, `*` for all languages.
+    pub language: Option<String>,
+    /// The `key` of the row of the article in `#__associations`, which the content table
+    /// export does not have, only a query joining it.
+    pub association: Option<String>,
+}
+
+/// Accepts numbers as well, for exports that do not quote them.
+pub(crate) fn string_or_number<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        String(String),
+        Number(i64),
+    }
+    Ok(
+        Option::<StringOrNumber>::deserialize(deserializer)?.map(|value| match value {
+            StringOrNumber::String(string) => string,
+            StringOrNumber::Number(number) => number.to_string(),
+        }),
+    )
+}
+
+/// The JSON export, either read from disk on every pass or mapped into memory once.
+pub enum JoomlaJson {
+    File(PathBuf),
+    Mapped(PathBuf, Mmap),
+}
+
+impl JoomlaJson {
+    /// Reads the export at `path` on every pass.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        JoomlaJson::File(path.into())
+    }
+
+    pub fn open(path: &Path, mmap: bool) -> Result<Self, ConverterError> {
+        if mmap {
+            let read_failed = |source| ConverterError::ReadFailed {
+                path: path.to_path_buf(),
+                source,
+            };
+            let file = File::open(path).map_err(read_failed)?;
+            // SAFETY: The export is not expected to change during a conversion. If it
+            // does anyway, parsing fails or produces garbage but memory stays valid.
+            let map = unsafe { Mmap::map(&file).map_err(read_failed)? };
+            Ok(JoomlaJson::Mapped(path.to_path_buf(), map))
+        } else {
+            Ok(JoomlaJson::new(path))
+        }
+    }
+
+    /// Calls `article` with the position and content of every entry of the `data`
+    /// array of the input, without loading the whole export into memory.
+    pub fn stream_entries<F>(&self, article: F) -> Result<(), ConverterError>
+    where
+        F: FnMut(usize, JoomlaRow),
+    {
+        let result = match self {
+            JoomlaJson::File(path) => {
+                let file = File::open(path).map_err(|source| ConverterError::ReadFailed {
+                    path: path.clone(),
+                    source,
+                })?;
+                let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(file));
+                deserializer.deserialize_map(ExportVisitor(article))
+            }
+            JoomlaJson::Mapped(_, map) => {
+                let mut deserializer = serde_json::Deserializer::from_slice(map);
+                deserializer.deserialize_map(ExportVisitor(article))
+            }
+        };
+        result.map_err(|error| {
+            let path = self.path().to_path_buf();
+            if error.is_io() {
+                ConverterError::ReadFailed {
+                    path,
+                    source: io::Error::from(error),
+                }
+            } else {
+                ConverterError::InputParse {
+                    path,
+                    message: error.to_string(),
+                }
+            }
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            JoomlaJson::File(path) | JoomlaJson::Mapped(path, _) => path,
+        }
+    }
+}
+
+impl ArticleSource for JoomlaJson {
+    fn stream_all(
+        &self,
+        article: &mut dyn FnMut(SourceArticle),
+        invalid: &mut dyn FnMut(ConverterError),
+    ) -> Result<(), ConverterError> {
+        self.stream_entries(
+            |ordinal, row| match row.into_source_article(ordinal, self.path()) {
+                Ok(Some(source_article)) => article(source_article),
+                Ok(None) => {}
+                Err(error) => invalid(error),
+            },
+        )
+    }
+
+    fn paths(&self) -> Vec<PathBuf> {
+        vec![self.path().to_path_buf()]
+    }
+}
+
+struct ExportVisitor<F>(F);
+
+impl<'de, F: FnMut(usize, JoomlaRow)> Visitor<'de> for ExportVisitor<F> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a Joomla table export")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<(), A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "data" {
+                map.next_value_seed(DataVisitor(&mut self.0))?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct DataVisitor<'a, F>(&'a mut F);
+
+impl<'de, F: FnMut(usize, JoomlaRow)> DeserializeSeed<'de> for DataVisitor<'_, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F: FnMut(usize, JoomlaRow)> Visitor<'de> for DataVisitor<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array of articles")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut ordinal = 0;
+        // Name the entry, the position of the error alone is hard to find in a huge export.
+        while let Some(row) = seq
+            .next_element::<JoomlaRow>()
+            .map_err(|error| de::Error::custom(format!("entry {}: {}", ordinal, error)))?
+        {
+            (self.0)(ordinal, row);
+            ordinal += 1;
+        }
+        Ok(())
+    }
+}
+
+impl JoomlaRow {
+    /// Rows without a creation or publication date or without a category are not
+    /// articles and skipped.
+    pub fn into_source_article(
+        self,
+        ordinal: usize,
+        path: &Path,
+    ) -> Result<Option<SourceArticle>, ConverterError> {
+        let Some(catid) = &self.catid else {
+            return Ok(None);
+        };
+        if self.created.is_none() && self.publish_up.is_none() {
+            return Ok(None);
+        }
+        let id = parse_number(&self.id);
+        let known_id = id.as_ref().ok().copied().flatten();
+        let missing_field = |field| ConverterError::MissingField {
+            article: ArticleRef::new(known_id, self.title.clone().unwrap_or_default()),
+            path: path.to_path_buf(),
+            field,
+        };
+        let id = id.map_err(|_| missing_field("id"))?;
+        let author_id = parse_number(&self.created_by).map_err(|_| missing_field("created_by"))?;
+
+        let parse = |date: &Option<String>| {
+            let date = date.as_deref()?;
+            NaiveDateTime::parse_from_str(date, DATE_TIME_FORMAT).ok()
+        };
+        // Zero dates like `0000-00-00 00:00:00` do not parse.
+        let created = parse(&self.created)
+            .or_else(|| parse(&self.publish_up))
+            .ok_or_else(|| missing_field("created"))?;
+        let catid = catid.parse().map_err(|_| missing_field("catid"))?;
+        let state = match self.state.as_deref().map(str::trim) {
+            None | Some("1") => State::Published,
+            Some("0") => State::Unpublished,
+            Some("2") => State::Archived,
+            Some("-2") => State::Trashed,
+            Some(_) => return Err(missing_field("state")),
+        };
+        let title = self.title.clone().ok_or_else(|| missing_field("title"))?;
+        let html = self
+            .introtext
+            .clone()
+            .ok_or_else(|| missing_field("introtext"))?;
+        Ok(Some(SourceArticle {
+            ordinal,
+            id,
+            title,
+            alias: self.alias.filter(|alias| !alias.is_empty()),
+            created,
+            catid: Some(catid),
+            html,
+            fulltext: self.fulltext.filter(|fulltext| !fulltext.trim().is_empty()),
+            tags: self
+                .metakey
+                .iter()
+                .flat_map(|metakey| metakey.split(','))
+                .map(str::trim)
+                .filter(|keyword| !keyword.is_empty())
+                .map(str::to_string)
+                .collect(),
+            categories: Vec::new(),
+            author_id,
+            author: self
+                .created_by_alias
+                .filter(|alias| !alias.trim().is_empty()),
+            state,
+            language: self
+                .language
+                .map(|language| language.trim().to_string())
+                .filter(|language| !language.is_empty() && language != "*"),
+            association: self
+                .association
+                .filter(|association| !association.trim().is_empty()),
+        }))
+    }
+}
+
+/// The number in `field`, `None` if the field is missing or empty like in the rows of
+/// exports without it.
+fn parse_number<T: FromStr>(field: &Option<String>) -> Result<Option<T>, T::Err> {
+    match field.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(number) => number.parse().map(Some),
+    }
+}
@@ -0,0 +1,55 @@
+//! Several inputs of one source read as one, like an export split into a file per year.
+
+use super::{ArticleSource, SourceArticle};
+use crate::error::ConverterError;
+use std::{collections::HashSet, path::PathBuf};
+
+/// The articles of `sources` in order, numbered one after the other. An article with the
+/// ID of an earlier one, which an overlapping export has twice, is left out.
+pub struct Merged {
+    sources: Vec<Box<dyn ArticleSource>>,
+}
+
+impl Merged {
+    pub fn new(sources: Vec<Box<dyn ArticleSource>>) -> Self {
+        Merged { sources }
+    }
+}
+
+impl ArticleSource for Merged {
+    fn stream_all(
+        &self,
+        article: &mut dyn FnMut(SourceArticle),
+        invalid: &mut dyn FnMut(ConverterError),
+    ) -> Result<(), ConverterError> {
+        let mut ids = HashSet::new();
+        let mut offset = 0;
+        for source in &self.sources {
+            let mut next = offset;
+            source.stream_all(
+                &mut |mut source_article| {
+                    source_article.ordinal += offset;
+                    next = next.max(source_article.ordinal + 1);
+                    if source_article.id.is_some_and(|id| !ids.insert(id)) {
+                        tracing::debug!(
+                            "Article {} is in the input twice, the later one is left out",
+                            source_article.id.unwrap_or_default()
+                        );
+                        return;
+                    }
+                    article(source_article);
+                },
+                invalid,
+            )?;
+            offset = next;
+        }
+        Ok(())
+    }
+
+    fn paths(&self) -> Vec<PathBuf> {
+        self.sources
+            .iter()
+            .flat_map(|source| source.paths())
+            .collect()
+    }
+}
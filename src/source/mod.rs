@@ -0,0 +1,169 @@
+//! Where the articles come from. Every supported CMS export or database is a module implementing
+//! [`ArticleSource`], registered in [`SOURCES`].
+
+use crate::{error::ConverterError, model::Image};
+use chrono::NaiveDateTime;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+#[cfg(feature = "database")]
+mod joomla_db;
+mod joomla_json;
+mod merged;
+mod wordpress;
+
+#[cfg(feature = "database")]
+pub use joomla_db::JoomlaDb;
+pub(crate) use joomla_json::{string_or_number, DATE_TIME_FORMAT};
+pub use joomla_json::{JoomlaJson, JoomlaRow};
+pub use merged::Merged;
+pub use wordpress::WordPress;
+
+/// Table prefix of Joomla 1.5, newer versions pick a random one on installation.
+pub const TABLE_PREFIX: &str = "jos_";
+
+/// An article as read from the source, before any cleanup.
+#[derive(Debug, Clone)]
+pub struct SourceArticle {
+    /// Position of the article within the source, stable between passes.
+    pub ordinal: usize,
+    /// ID of the article in the CMS.
+    pub id: Option<u64>,
+    pub title: String,
+    /// URL friendly name of the article.
+    pub alias: Option<String>,
+    pub created: NaiveDateTime,
+    /// The category the article belongs to, if the source has numeric categories.
+    pub catid: Option<u32>,
+    /// The body of the article as HTML, the part before the "read more" in Joomla.
+    pub html: String,
+    /// The part of the body after the "read more", if the article has one.
+    pub fulltext: Option<String>,
+    /// Tags or keywords, like the meta keywords in Joomla.
+    pub tags: Vec<String>,
+    /// Names of the categories, if the source has them, otherwise they are looked up
+    /// in [`Converter::categories`](crate::Converter::categories) by the `catid`.
+    pub categories: Vec<String>,
+    /// The user who wrote the article, if the source has numeric users, to look up in
+    /// [`Converter::authors`](crate::Converter::authors).
+    pub author_id: Option<u64>,
+    /// Name the article is signed with, like `created_by_alias` in Joomla.
+    pub author: Option<String>,
+    pub state: State,
+    /// Language tag like `de-DE`, `None` if the article is in all languages.
+    pub language: Option<String>,
+    /// Shared by the translations of an article, like the key of its Joomla association.
+    pub association: Option<String>,
+}
+
+impl SourceArticle {
+    /// SHA-256 of the body of the article and the sources of its `images` as its HTML
+    /// links them, which only changes with the article in the source.
+    pub fn hash(&self, images: &[Image]) -> String {
+        let mut hash = Sha256::new();
+        hash.update(&self.html);
+        // Separated, so that moving text between the parts changes the hash.
+        hash.update([0]);
+        hash.update(self.fulltext.as_deref().unwrap_or_default());
+        for image in images {
+            hash.update([0]);
+            hash.update(image.source.to_string_lossy().as_bytes());
+        }
+        format!("{:x}", hash.finalize())
+    }
+}
+
+/// Whether an article is shown on the website, the `state` of a Joomla article.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum State {
+    #[default]
+    Published,
+    /// Not shown yet or any more, like a draft.
+    Unpublished,
+    /// Shown in the archive of the website.
+    Archived,
+    Trashed,
+}
+
+impl State {
+    /// Whether articles in this state are converted. The unpublished ones only as
+    /// `drafts`, the trashed ones never.
+    pub fn is_converted(self, drafts: bool) -> bool {
+        match self {
+            State::Published | State::Archived => true,
+            State::Unpublished => drafts,
+            State::Trashed => false,
+        }
+    }
+}
+
+/// A source of articles, e.g. a CMS export.
+///
+/// The converter makes two passes over a source, so every call must yield the same
+/// articles in the same order.
+pub trait ArticleSource: Sync {
+    /// Calls `article` for every article of the source and `invalid` with the
+    /// [`MissingField`](ConverterError::MissingField) of every entry that is not a valid
+    /// article, like one without a title, and goes on with the next. Only failures to
+    /// read the source end it.
+    fn stream_all(
+        &self,
+        article: &mut dyn FnMut(SourceArticle),
+        invalid: &mut dyn FnMut(ConverterError),
+    ) -> Result<(), ConverterError>;
+
+    /// Calls `article` for every article of the source, failing with the first invalid
+    /// entry once the source is read.
+    fn stream(&self, article: &mut dyn FnMut(SourceArticle)) -> Result<(), ConverterError> {
+        let mut first = None;
+        self.stream_all(article, &mut |error| {
+            first.get_or_insert(error);
+        })?;
+        first.map_or(Ok(()), Err)
+    }
+
+    /// The files the source reads, to convert again when they change.
+    fn paths(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
+}
+
+/// What a source may be configured with on the command line.
+#[derive(Debug, Clone, Default)]
+pub struct SourceOptions {
+    pub input: PathBuf,
+    /// Map the input into memory instead of reading it, for sources that support it.
+    pub mmap: bool,
+    /// The database to read instead of the input, for sources that read one.
+    pub db_url: Option<String>,
+    /// What `#__` stands for in the names of the Joomla tables.
+    pub db_prefix: String,
+}
+
+type SourceConstructor = fn(&SourceOptions) -> Result<Box<dyn ArticleSource>, ConverterError>;
+
+/// All known sources by name.
+pub const SOURCES: &[(&str, SourceConstructor)] = &[
+    ("joomla-json", |options| {
+        Ok(Box::new(JoomlaJson::open(&options.input, options.mmap)?))
+    }),
+    ("wordpress-wxr", |options| {
+        Ok(Box::new(WordPress::new(&options.input)))
+    }),
+    #[cfg(feature = "database")]
+    ("joomla-db", |options| {
+        let url = options.db_url.clone().unwrap_or_default();
+        Ok(Box::new(JoomlaDb::new(url, &options.db_prefix)))
+    }),
+];
+
+/// Creates the source registered as `name`.
+pub fn create(
+    name: &str,
+    options: &SourceOptions,
+) -> Option<Result<Box<dyn ArticleSource>, ConverterError>> {
+    SOURCES
+        .iter()
+        .find(|(source_name, _)| *source_name == name)
+        .map(|(_, constructor)| constructor(options))
+}
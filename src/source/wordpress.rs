@@ -0,0 +1,326 @@
+//! The WordPress eXtended RSS export, as written by Tools → Export in WordPress.
+
+use super::{ArticleSource, SourceArticle, State, DATE_TIME_FORMAT};
+use crate::error::{ArticleRef, ConverterError};
+use chrono::NaiveDateTime;
+use quick_xml::{events::Event, Reader};
+use regex::Regex;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+/// The posts of a WXR export. Their category is the WordPress term ID of their first
+/// category, their author the display name of the user who wrote them. Their images
+/// are relative to the WordPress directory when they are uploads of the blog. Images
+/// attached to a post but not shown in its content, like galleries, are added after
+/// the content.
+///
+/// Only posts are read, not pages. Drafts, posts waiting for review, scheduled and
+/// private ones are unpublished, drafts without a date are skipped.
+#[derive(Debug, Clone)]
+pub struct WordPress {
+    path: PathBuf,
+}
+
+/// What the export has of an `<item>`, a post, page or attachment.
+#[derive(Debug, Default)]
+struct Item {
+    id: Option<u64>,
+    title: String,
+    name: Option<String>,
+    date: String,
+    post_type: String,
+    status: String,
+    parent: Option<u64>,
+    content: String,
+    attachment_url: Option<String>,
+    /// Login of the author.
+    creator: Option<String>,
+    /// The `nicename` of the categories, not of the tags.
+    categories: Vec<String>,
+    category_names: Vec<String>,
+    tags: Vec<String>,
+}
+
+/// The whole export, attachments may come after the posts they belong to.
+#[derive(Debug, Default)]
+struct Export {
+    base_url: String,
+    /// Term IDs of the categories by their `nicename`.
+    categories: HashMap<String, u32>,
+    /// User ID and display name of the authors by their login.
+    authors: HashMap<String, (Option<u64>, String)>,
+    items: Vec<Item>,
+}
+
+impl WordPress {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        WordPress { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn read(&self) -> Result<Export, ConverterError> {
+        let file = File::open(&self.path).map_err(|source| ConverterError::ReadFailed {
+            path: self.path.clone(),
+            source,
+        })?;
+        let mut reader = Reader::from_reader(BufReader::new(file));
+        reader.config_mut().trim_text(true);
+        let parse_failed = |error: quick_xml::Error| ConverterError::InputParse {
+            path: self.path.clone(),
+            message: error.to_string(),
+        };
+
+        let mut export = Export::default();
+        let mut item: Option<Item> = None;
+        // The elements the reader is in, with the text read within the innermost one.
+        let mut elements: Vec<String> = Vec::new();
+        let mut text = String::new();
+        let mut term: (Option<u32>, Option<String>) = (None, None);
+        let mut author: (Option<u64>, Option<String>, Option<String>) = (None, None, None);
+        // Whether the current `<category>` of an item is a category or a tag.
+        let mut domain = Vec::new();
+        let mut buffer = Vec::new();
+        loop {
+            match reader.read_event_into(&mut buffer).map_err(parse_failed)? {
+                Event::Start(start) => {
+                    let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+                    match name.as_str() {
+                        "item" => item = Some(Item::default()),
+                        "category" => {
+                            domain = start
+                                .try_get_attribute("domain")
+                                .ok()
+                                .flatten()
+                                .map(|domain| domain.value.into_owned())
+                                .unwrap_or_default();
+                            let nicename = start.try_get_attribute("nicename").ok().flatten();
+                            if let (Some(item), Some(nicename)) = (&mut item, nicename) {
+                                if domain == b"category" {
+                                    item.categories.push(
+                                        String::from_utf8_lossy(&nicename.value).into_owned(),
+                                    );
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                    elements.push(name);
+                    text.clear();
+                }
+                Event::Text(content) => {
+                    text.push_str(&content.unescape().map_err(parse_failed)?);
+                }
+                Event::CData(content) => {
+                    text.push_str(&String::from_utf8_lossy(&content.into_inner()));
+                }
+                Event::End(_) => {
+                    let name = elements.pop().unwrap_or_default();
+                    let value = std::mem::take(&mut text);
+                    match (&mut item, name.as_str()) {
+                        (Some(_), "item") => export.items.extend(item.take()),
+                        (Some(item), "title") => item.title = value,
+                        (Some(item), "wp:post_id") => item.id = value.parse().ok(),
+                        (Some(item), "wp:post_name") => item.name = Some(value),
+                        (Some(item), "wp:post_date") => item.date = value,
+                        (Some(item), "wp:post_type") => item.post_type = value,
+                        (Some(item), "wp:status") => item.status = value,
+                        (Some(item), "wp:post_parent") => item.parent = value.parse().ok(),
+                        (Some(item), "content:encoded") => item.content = value,
+                        (Some(item), "wp:attachment_url") => item.attachment_url = Some(value),
+                        (Some(item), "dc:creator") => item.creator = Some(value),
+                        (Some(item), "category") => match domain.as_slice() {
+                            b"category" => item.category_names.push(value),
+                            b"post_tag" => item.tags.push(value),
+                            _ => {}
+                        },
+                        (None, "wp:base_site_url") => export.base_url = value,
+                        (None, "wp:term_id") => term.0 = value.parse().ok(),
+                        (None, "wp:category_nicename") => term.1 = Some(value),
+                        (None, "wp:category") => {
+                            if let (Some(id), Some(nicename)) = (term.0.take(), term.1.take()) {
+                                export.categories.insert(nicename, id);
+                            }
+                        }
+                        (None, "wp:author_id") => author.0 = value.parse().ok(),
+                        (None, "wp:author_login") => author.1 = Some(value),
+                        (None, "wp:author_display_name") => author.2 = Some(value),
+                        (None, "wp:author") => {
+                            if let (id, Some(login), Some(name)) = std::mem::take(&mut author) {
+                                export.authors.insert(login, (id, name));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            buffer.clear();
+        }
+        Ok(export)
+    }
+}
+
+impl ArticleSource for WordPress {
+    fn stream_all(
+        &self,
+        article: &mut dyn FnMut(SourceArticle),
+        invalid: &mut dyn FnMut(ConverterError),
+    ) -> Result<(), ConverterError> {
+        let export = self.read()?;
+        let uploads = export.uploads();
+        let mut attachments: HashMap<u64, Vec<String>> = HashMap::new();
+        for item in &export.items {
+            if let (Some(parent), Some(url)) = (item.parent, &item.attachment_url) {
+                if item.post_type == "attachment" && parent != 0 {
+                    attachments
+                        .entry(parent)
+                        .or_default()
+                        .push(export.local(url));
+                }
+            }
+        }
+
+        let posts = export.items.iter().filter_map(|item| {
+            let state = match item.status.as_str() {
+                "publish" => State::Published,
+                "draft" | "pending" | "future" | "private" => State::Unpublished,
+                "trash" => State::Trashed,
+                // Auto-drafts are only a new post opened in the editor.
+                _ => return None,
+            };
+            (item.post_type == "post").then_some((item, state))
+        });
+        for (ordinal, (item, state)) in posts.enumerate() {
+            let created = match NaiveDateTime::parse_from_str(&item.date, DATE_TIME_FORMAT) {
+                Ok(created) => created,
+                // Drafts that were never scheduled have a zero date.
+                Err(_) if state != State::Published => continue,
+                Err(_) => {
+                    invalid(ConverterError::MissingField {
+                        article: ArticleRef::new(item.id, item.title.clone()),
+                        path: self.path.clone(),
+                        field: "wp:post_date",
+                    });
+                    continue;
+                }
+            };
+            let author = item
+                .creator
+                .as_ref()
+                .and_then(|login| export.authors.get(login));
+            let content = uploads.replace_all(&item.content, "${1}").into_owned();
+            let content = autop(&content);
+            let (intro, more) = match content.split_once("<!--more-->") {
+                Some((intro, more)) => (intro.to_string(), Some(more.to_string())),
+                None => (content, None),
+            };
+            let mut gallery = String::new();
+            for path in item
+                .id
+                .and_then(|id| attachments.get(&id))
+                .into_iter()
+                .flatten()
+            {
+                let stem = path
+                    .rsplit_once('.')
+                    .map_or(path.as_str(), |(stem, _)| stem);
+                if !intro.contains(stem) && !more.as_deref().unwrap_or_default().contains(stem) {
+                    gallery.push_str(&format!("<img src=\"{}\" />", path));
+                }
+            }
+            let (html, fulltext) = match more {
+                Some(more) => (intro, Some(more + &gallery)),
+                None => (intro + &gallery, None),
+            };
+            article(SourceArticle {
+                ordinal,
+                id: item.id,
+                title: item.title.clone(),
+                alias: item.name.clone().filter(|name| !name.is_empty()),
+                created,
+                catid: item
+                    .categories
+                    .iter()
+                    .find_map(|nicename| export.categories.get(nicename).copied()),
+                html,
+                fulltext: fulltext.filter(|fulltext| !fulltext.trim().is_empty()),
+                tags: item.tags.clone(),
+                categories: item.category_names.clone(),
+                author_id: author.and_then(|(id, _)| *id),
+                author: author
+                    .map(|(_, name)| name.clone())
+                    .filter(|name| !name.trim().is_empty()),
+                state,
+                language: None,
+                association: None,
+            });
+        }
+        Ok(())
+    }
+
+    fn paths(&self) -> Vec<PathBuf> {
+        vec![self.path.clone()]
+    }
+}
+
+impl Export {
+    /// The URL of the blog without its scheme, as either scheme may be linked.
+    fn host(&self) -> &str {
+        self.base_url
+            .split_once("://")
+            .map_or(self.base_url.as_str(), |(_, host)| host)
+            .trim_end_matches('/')
+    }
+
+    /// Matches the URL of the blog in the `src` of an image, keeping what is before it.
+    fn uploads(&self) -> Regex {
+        Regex::new(&format!(
+            r#"(<img\b[^>]*?\bsrc=["'])(?:https?:)?//{}/"#,
+            regex::escape(self.host())
+        ))
+        .expect("The escaped host makes a valid regex")
+    }
+
+    /// `url` relative to the WordPress directory if it is one of the blog.
+    fn local(&self, url: &str) -> String {
+        let without_scheme = ["https:", "http:"]
+            .iter()
+            .find_map(|scheme| url.strip_prefix(scheme))
+            .unwrap_or(url);
+        without_scheme
+            .strip_prefix("//")
+            .and_then(|rest| rest.strip_prefix(self.host()))
+            .and_then(|rest| rest.strip_prefix('/'))
+            .unwrap_or(url)
+            .to_string()
+    }
+}
+
+/// Paragraphs for content without them, which WordPress separates by blank lines and
+/// adds the `<p>` on display only.
+fn autop(content: &str) -> String {
+    if content.contains("<p") {
+        return content.to_string();
+    }
+    content
+        .replace("\r\n", "\n")
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| {
+            if paragraph == "<!--more-->" {
+                paragraph.to_string()
+            } else {
+                format!("<p>{}</p>", paragraph.replace('\n', "<br />"))
+            }
+        })
+        .collect()
+}
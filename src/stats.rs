@@ -0,0 +1,169 @@
+//! Statistics of the missions for the charts of the statistics page, which the old
+//! website computed on every request, see [`Stats`], and of the whole input, to check an
+//! export before converting it, see [`InputStats`].
+
+use crate::{
+    error::ConverterError, html, render::RenderedArticle, source::SourceArticle, Converter,
+};
+use chrono::{Datelike, NaiveDateTime};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Where the statistics go in the output directory, read by the templates as
+/// `site.Data.stats`.
+pub const STATS_FILE: &str = "data/stats.json";
+
+/// Alarm type of titles without one.
+const OTHER_ALARM_TYPE: &str = "Sonstige";
+
+/// Counts with their labels in order, as charting libraries take them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Series {
+    pub labels: Vec<String>,
+    pub missions: Vec<usize>,
+    pub images: Vec<usize>,
+}
+
+impl Series {
+    fn from_counts(counts: BTreeMap<String, (usize, usize)>) -> Self {
+        let mut series = Series::default();
+        for (label, (missions, images)) in counts {
+            series.labels.push(label);
+            series.missions.push(missions);
+            series.images.push(images);
+        }
+        series
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Stats {
+    pub missions: usize,
+    pub images: usize,
+    pub per_year: Series,
+    /// Labeled as `YYYY-MM`, months without missions are left out.
+    pub per_month: Series,
+    /// Taken from the titles, which start with the type, like `Brand: Küche`.
+    pub per_alarm_type: Series,
+}
+
+impl Stats {
+    /// Counts the articles of `previews`, as returned by [`Converter::preview`](crate::Converter::preview).
+    pub fn new(previews: &[(SourceArticle, RenderedArticle)]) -> Self {
+        let mut per_year = BTreeMap::new();
+        let mut per_month = BTreeMap::new();
+        let mut per_alarm_type = BTreeMap::new();
+        let mut stats = Stats::default();
+        for (_, rendered) in previews {
+            let article = &rendered.article;
+            let images = article.images.len();
+            let month = format!("{}-{:02}", article.date.year(), article.date.month());
+            for (counts, label) in [
+                (&mut per_year, rendered.year.to_string()),
+                (&mut per_month, month),
+                (&mut per_alarm_type, alarm_type(&article.title).to_string()),
+            ] {
+                let (missions_count, images_count) = counts.entry(label).or_insert((0, 0));
+                *missions_count += 1;
+                *images_count += images;
+            }
+            stats.missions += 1;
+            stats.images += images;
+        }
+        stats.per_year = Series::from_counts(per_year);
+        stats.per_month = Series::from_counts(per_month);
+        stats.per_alarm_type = Series::from_counts(per_alarm_type);
+        stats
+    }
+}
+
+fn alarm_type(title: &str) -> &str {
+    match title.split_once(':') {
+        Some((alarm_type, _)) if !alarm_type.trim().is_empty() => alarm_type.trim(),
+        _ => OTHER_ALARM_TYPE,
+    }
+}
+
+/// What a part of the input adds up to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Counts {
+    pub articles: usize,
+    pub images: usize,
+    /// Words of the text, intro and full text.
+    pub words: usize,
+}
+
+impl Counts {
+    fn add(&mut self, images: usize, words: usize) {
+        self.articles += 1;
+        self.images += images;
+        self.words += words;
+    }
+
+    /// Average words per article, zero without articles.
+    pub fn average_words(&self) -> usize {
+        self.words.checked_div(self.articles).unwrap_or_default()
+    }
+}
+
+/// Statistics of every article of the input, whatever its year, category and state.
+/// Nothing is converted, the years and categories to convert can be chosen from them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct InputStats {
+    pub total: Counts,
+    pub per_year: BTreeMap<i32, Counts>,
+    /// By the ID of the category, `None` for the articles without one.
+    pub per_category: BTreeMap<Option<u32>, Counts>,
+    /// Names of the categories by their ID, as far as the source or the
+    /// [`categories`](Converter::categories) know them.
+    pub category_names: BTreeMap<u32, String>,
+    /// Creation date of the oldest article.
+    pub first: Option<NaiveDateTime>,
+    /// Creation date of the newest article.
+    pub last: Option<NaiveDateTime>,
+}
+
+impl InputStats {
+    /// Reads the input of `converter` once.
+    pub fn new(converter: &Converter) -> Result<Self, ConverterError> {
+        let mut stats = InputStats::default();
+        converter.source.stream_all(
+            &mut |mut source| {
+                converter.fill_names(&mut source);
+                stats.add(&source);
+            },
+            &mut |error| tracing::warn!("{}", error),
+        )?;
+        Ok(stats)
+    }
+
+    fn add(&mut self, source: &SourceArticle) {
+        let parts = [Some(source.html.as_str()), source.fulltext.as_deref()];
+        let (images, words) = parts
+            .into_iter()
+            .flatten()
+            .fold((0, 0), |(images, words), html| {
+                (
+                    images + html::images(html).len(),
+                    words + html::word_count(html),
+                )
+            });
+        self.total.add(images, words);
+        self.per_year
+            .entry(source.created.year())
+            .or_default()
+            .add(images, words);
+        self.per_category
+            .entry(source.catid)
+            .or_default()
+            .add(images, words);
+        if let (Some(catid), Some(name)) = (source.catid, source.categories.first()) {
+            self.category_names
+                .entry(catid)
+                .or_insert_with(|| html::decode_entities(name));
+        }
+        let created = source.created;
+        self.first = Some(self.first.map_or(created, |first| first.min(created)));
+        self.last = Some(self.last.map_or(created, |last| last.max(created)));
+    }
+}
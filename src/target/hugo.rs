@@ -0,0 +1,481 @@
+use super::{fields, template, thumbnail, FrontmatterKeys, OutputTarget, Strings, TargetOptions};
+use crate::model::{slugify, Article, Image, ImageName, ImageNumber, Section, BUNDLE_THUMBNAIL};
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+use std::io::{self, Write};
+
+/// The permalinks in the [site configuration](OutputTarget::site_config) of the articles
+/// in `dir`, the directory of their section or the [`root`](TargetOptions::root).
+fn permalink(dir: &str) -> String {
+    format!("/{}/:year/:slug/", dir)
+}
+
+/// Hugo page bundles with a YAML frontmatter and `image` shortcodes, or what the
+/// [`Templates`](super::Templates) of the options make of them.
+#[derive(Debug, Clone, Default)]
+pub struct Hugo {
+    pub options: TargetOptions,
+}
+
+impl Hugo {
+    pub fn new(options: TargetOptions) -> Self {
+        Hugo { options }
+    }
+}
+
+/// The frontmatter of an article, written with the keys of [`FrontmatterKeys`].
+#[derive(Debug, Serialize)]
+struct Frontmatter {
+    title: String,
+    date: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    draft: bool,
+    description: String,
+    thumbnail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    categories: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    resources: Vec<Resource>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    converter_hash: Option<String>,
+    /// The extra fields, which go next to the others in the YAML frontmatter.
+    #[serde(skip_serializing_if = "serde_json::Map::is_empty")]
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The frontmatter of a section index, serialized so that titles like `Einsätze: 2019`
+/// are quoted.
+#[derive(Debug, Serialize)]
+struct IndexFrontmatter<'a> {
+    title: &'a str,
+    /// Only in the year indexes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nested: Option<bool>,
+}
+
+impl IndexFrontmatter<'_> {
+    fn to_index(&self) -> String {
+        let yaml = serde_yaml::to_string(self).expect("The index frontmatter always serializes");
+        format!("---\n{}---\n", yaml)
+    }
+}
+
+/// What the image template is given.
+#[derive(Debug, Serialize)]
+struct ImageData<'a> {
+    name: String,
+    src: String,
+    alt: Option<&'a str>,
+    caption: Option<&'a str>,
+}
+
+impl<'a> ImageData<'a> {
+    fn new(image: &'a Image, resource: &Resource, strings: &'a Strings) -> Self {
+        ImageData {
+            name: resource.name.clone(),
+            src: resource.src.clone(),
+            alt: strings.alt(image),
+            caption: image.caption.as_deref(),
+        }
+    }
+}
+
+/// What the gallery template is given.
+#[derive(Debug, Serialize)]
+struct GalleryData<'a> {
+    dir: &'a str,
+    images: Vec<ImageData<'a>>,
+}
+
+/// A page resource, an image of the article bundle.
+#[derive(Debug, Serialize)]
+struct Resource {
+    name: String,
+    src: String,
+}
+
+impl Frontmatter {
+    /// Serializes to YAML, which quotes and escapes where needed.
+    fn to_yaml(&self, keys: &FrontmatterKeys) -> io::Result<String> {
+        let Value::Mapping(fields) = serde_yaml::to_value(self).map_err(io::Error::other)? else {
+            unreachable!("A struct serializes to a mapping");
+        };
+        let mut renamed = Mapping::new();
+        for (field, value) in fields {
+            let key = match field.as_str() {
+                Some("title") => &keys.title,
+                Some("date") => &keys.date,
+                Some("draft") => &keys.draft,
+                Some("description") => &keys.description,
+                Some("thumbnail") => &keys.thumbnail,
+                Some("author") => &keys.author,
+                Some("tags") => &keys.tags,
+                Some("categories") => &keys.categories,
+                Some("aliases") => &keys.aliases,
+                Some("resources") => &keys.resources,
+                Some("fields") => {
+                    // A field with the key of a built-in one takes its place.
+                    let Value::Mapping(extra) = value else {
+                        unreachable!("A map serializes to a mapping");
+                    };
+                    renamed.extend(extra);
+                    continue;
+                }
+                Some("source_hash") => &keys.source_hash,
+                _ => &keys.converter_hash,
+            };
+            renamed.insert(Value::String(key.clone()), value);
+        }
+        serde_yaml::to_string(&renamed).map_err(io::Error::other)
+    }
+}
+
+impl OutputTarget for Hugo {
+    fn options(&self) -> &TargetOptions {
+        &self.options
+    }
+
+    fn write_article(
+        &self,
+        output: &mut dyn Write,
+        article: &Article,
+        year: u32,
+        index: usize,
+    ) -> io::Result<()> {
+        let options = &self.options;
+        let frontmatter = Frontmatter {
+            title: article.title.clone(),
+            date: options.format_date(article),
+            draft: article.draft,
+            description: options.description.of(article),
+            thumbnail: thumbnail(article, year, index, options),
+            author: article.author.clone(),
+            tags: article.tags.clone(),
+            categories: article.categories.clone(),
+            // Hugo writes a page per alias, which cannot have a query.
+            aliases: article
+                .aliases
+                .iter()
+                .filter(|alias| !alias.contains('?'))
+                .cloned()
+                .collect(),
+            resources: (0..article.images.len())
+                .map(|image_index| Resource {
+                    name: format!("img-{}", ImageNumber(image_index)),
+                    src: format!("img/{}", ImageName::of(article, year, index, image_index)),
+                })
+                // After the images, which the image template finds by their number.
+                .chain(
+                    (article.bundle_thumbnail && !article.images.is_empty()).then(|| Resource {
+                        name: BUNDLE_THUMBNAIL.to_string(),
+                        src: article.bundle_thumbnail_name(),
+                    }),
+                )
+                .collect(),
+            source_hash: article.source_hash.clone(),
+            converter_hash: article.converter_hash.clone(),
+            fields: fields(article, year, index, options)?,
+        };
+        match &options.templates.frontmatter {
+            Some(template) => {
+                let rendered = template::render(template, &frontmatter)?;
+                output.write_all(rendered.trim_end().as_bytes())?;
+                writeln!(output, "\n")?;
+            }
+            None => {
+                writeln!(output, "---")?;
+                output.write_all(frontmatter.to_yaml(&options.frontmatter)?.as_bytes())?;
+                writeln!(output, "---\n")?;
+            }
+        }
+        output.write_all(article.text.as_bytes())?;
+        // Only the first image stays inline, the gallery has all of them.
+        let gallery = options
+            .templates
+            .gallery
+            .as_ref()
+            .filter(|_| article.images.len() > 1);
+        let inline = if gallery.is_some() {
+            1
+        } else {
+            article.images.len()
+        };
+        for (image_index, image) in article.images.iter().enumerate().take(inline) {
+            if let Some(template) = &options.templates.image {
+                let data =
+                    ImageData::new(image, &frontmatter.resources[image_index], &options.strings);
+                writeln!(
+                    output,
+                    "{}  ",
+                    template::render(template, &data)?.trim_end()
+                )?;
+                continue;
+            }
+            write!(
+                output,
+                "{{{{< image src=\"img-{}\"",
+                ImageNumber(image_index)
+            )?;
+            if let Some(alt) = options.strings.alt(image) {
+                write!(output, " alt=\"{}\"", shortcode_escape(alt))?;
+            }
+            if let Some(caption) = &image.caption {
+                write!(output, " caption=\"{}\"", shortcode_escape(caption))?;
+            }
+            writeln!(output, " >}}}}  ")?;
+        }
+        if let Some(template) = gallery {
+            let data = GalleryData {
+                dir: "img",
+                images: article
+                    .images
+                    .iter()
+                    .enumerate()
+                    .map(|(image_index, image)| {
+                        ImageData::new(image, &frontmatter.resources[image_index], &options.strings)
+                    })
+                    .collect(),
+            };
+            writeln!(
+                output,
+                "{}  ",
+                template::render(template, &data)?.trim_end()
+            )?;
+        }
+        Ok(())
+    }
+
+    fn source_hash(&self, content: &str) -> Option<String> {
+        frontmatter_string(content, &self.options.frontmatter.source_hash)
+    }
+
+    fn converter_hash(&self, content: &str) -> Option<String> {
+        frontmatter_string(content, &self.options.frontmatter.converter_hash)
+    }
+
+    fn section_index(&self, section: &Section, year: u32) -> Option<String> {
+        let title = self.options.strings.year_title(section, year);
+        Some(
+            IndexFrontmatter {
+                title: &title,
+                nested: Some(false),
+            }
+            .to_index(),
+        )
+    }
+
+    fn section_dir_index(&self, section: &Section) -> Option<String> {
+        Some(
+            IndexFrontmatter {
+                title: self.options.strings.section_title(section),
+                nested: None,
+            }
+            .to_index(),
+        )
+    }
+
+    /// For `hugo new`, which titles the article after its directory.
+    fn archetype(&self) -> Option<String> {
+        let keys = &self.options.frontmatter;
+        Some(format!(
+            concat!(
+                "---\n",
+                "{}: '{{{{ replace .File.ContentBaseName \"-\" \" \" | title }}}}'\n",
+                "{}: {{{{ .Date }}}}\n",
+                "{}: true\n",
+                "{}: ''\n",
+                "{}: ''\n",
+                "{}: []\n",
+                "{}: []\n",
+                "---\n",
+            ),
+            keys.title,
+            keys.date,
+            keys.draft,
+            keys.description,
+            keys.thumbnail,
+            keys.tags,
+            keys.categories,
+        ))
+    }
+
+    fn article_url(&self, article: &Article, year: u32, _index: usize) -> Option<String> {
+        Some(
+            permalink(article.section.as_deref().unwrap_or(&self.options.root))
+                .replace(":year", &year.to_string())
+                .replace(":slug", &urlize(&article.title)),
+        )
+    }
+
+    fn article_link(&self, path: &str) -> Option<String> {
+        Some(format!("{{{{< relref \"/{}\" >}}}}", path))
+    }
+
+    fn site_config(&self, sections: &[Section], years: &[u32]) -> Option<String> {
+        let mut config = String::from(
+            "# Merge into the configuration of the site, every year is a section.\n\n",
+        );
+        config.push_str("[taxonomies]\n");
+        config.push_str("  einsatzart = \"einsatzarten\"\n");
+        config.push_str("  tag = \"tags\"\n");
+        config.push_str("  category = \"categories\"\n\n");
+        // Without a `slug` in the frontmatter Hugo derives it from the title.
+        config.push_str("[permalinks]\n");
+        for section in sections {
+            match &section.dir {
+                Some(dir) => config.push_str(&format!("  \"{}\" = \"{}\"\n", dir, permalink(dir))),
+                None => {
+                    let permalink = permalink(&self.options.root);
+                    for year in years {
+                        config.push_str(&format!("  \"{}\" = \"{}\"\n", year, permalink));
+                    }
+                }
+            }
+        }
+        config.push_str(concat!(
+            "\n[related]\n",
+            "  includeNewer = true\n",
+            "  threshold = 80\n",
+            "  toLower = true\n",
+            "  [[related.indices]]\n",
+            "    name = \"einsatzarten\"\n",
+            "    weight = 100\n",
+            "  [[related.indices]]\n",
+            "    name = \"tags\"\n",
+            "    weight = 80\n",
+            "  [[related.indices]]\n",
+            "    name = \"date\"\n",
+            "    weight = 10\n",
+        ));
+        Some(config)
+    }
+
+    fn cms_config(&self, sections: &[Section], years: &[u32]) -> Option<String> {
+        let keys = &self.options.frontmatter;
+        let strings = &self.options.strings;
+        let labels = &strings.cms;
+        let mut config = String::from(concat!(
+            "# Merge into static/admin/config.yml of the site, the same fields as the frontmatter.\n",
+            "collections:\n",
+        ));
+        for (section, year) in sections
+            .iter()
+            .flat_map(|section| years.iter().map(move |year| (section, year)))
+        {
+            config.push_str(&format!(
+                concat!(
+                    "  - name: {name}-{year}\n",
+                    "    label: {label}\n",
+                    "    folder: content/{folder}\n",
+                    "    create: true\n",
+                    "    path: \"{{{{slug}}}}/index\"\n",
+                    "    media_folder: img\n",
+                    "    public_folder: img\n",
+                    "    fields:\n",
+                    "      - {{ label: {title_label}, name: {title}, widget: string }}\n",
+                    "      - {{ label: {date_label}, name: {date}, widget: datetime, format: \"{date_format}\" }}\n",
+                    "      - {{ label: {draft_label}, name: {draft}, widget: boolean, required: false }}\n",
+                    "      - {{ label: {description_label}, name: {description}, widget: string }}\n",
+                    "      - {{ label: {thumbnail_label}, name: {thumbnail}, widget: string, required: false }}\n",
+                    "      - {{ label: {author_label}, name: {author}, widget: string, required: false }}\n",
+                    "      - {{ label: {tags_label}, name: {tags}, widget: list, required: false }}\n",
+                    "      - {{ label: {categories_label}, name: {categories}, widget: list, required: false }}\n",
+                    "      - label: {images_label}\n",
+                    "        name: {resources}\n",
+                    "        widget: list\n",
+                    "        required: false\n",
+                    "        fields:\n",
+                    "          - {{ label: {image_name_label}, name: name, widget: string }}\n",
+                    "          - {{ label: {image_label}, name: src, widget: image }}\n",
+                    "      - {{ label: {text_label}, name: body, widget: markdown }}\n",
+                ),
+                name = match &section.dir {
+                    Some(dir) => dir.clone(),
+                    None => slugify(strings.section_title(section)),
+                },
+                label = strings.year_title(section, *year),
+                folder = section.join(year),
+                year = year,
+                title = keys.title,
+                draft = keys.draft,
+                date = keys.date,
+                date_format = moment_format(self.options.date_format()),
+                description = keys.description,
+                thumbnail = keys.thumbnail,
+                author = keys.author,
+                tags = keys.tags,
+                categories = keys.categories,
+                resources = keys.resources,
+                title_label = labels.title,
+                date_label = labels.date,
+                draft_label = labels.draft,
+                description_label = labels.description,
+                thumbnail_label = labels.thumbnail,
+                author_label = labels.author,
+                tags_label = labels.tags,
+                categories_label = labels.categories,
+                images_label = labels.images,
+                image_name_label = labels.image_name,
+                image_label = labels.image,
+                text_label = labels.text,
+            ));
+        }
+        Some(config)
+    }
+}
+
+/// The date format of Decap, which uses the tokens of Moment.js, for a chrono format.
+fn moment_format(format: &str) -> String {
+    [
+        ("%:z", "Z"),
+        ("%Y", "YYYY"),
+        ("%m", "MM"),
+        ("%d", "DD"),
+        ("%H", "HH"),
+        ("%M", "mm"),
+        ("%S", "ss"),
+    ]
+    .iter()
+    .fold(format.to_string(), |format, (chrono, moment)| {
+        format.replace(chrono, moment)
+    })
+}
+
+/// `value` for a quoted shortcode parameter.
+fn shortcode_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// The slug Hugo derives from a title, like its `urlize`: whitespace becomes dashes, what
+/// is not allowed in a path is dropped and letters are lowercased, accents included.
+fn urlize(title: &str) -> String {
+    title
+        .trim()
+        .chars()
+        .filter_map(|character| match character {
+            character if character.is_whitespace() => Some('-'),
+            character if character.is_alphanumeric() || "._-~+#".contains(character) => {
+                Some(character)
+            }
+            _ => None,
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The string at `key` in the YAML frontmatter of `content`, an article file.
+fn frontmatter_string(content: &str, key: &str) -> Option<String> {
+    let frontmatter = content.strip_prefix("---\n")?.split("\n---\n").next()?;
+    let Value::Mapping(fields) = serde_yaml::from_str(frontmatter).ok()? else {
+        return None;
+    };
+    Some(fields.get(key)?.as_str()?.to_string())
+}
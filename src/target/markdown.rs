@@ -0,0 +1,47 @@
+use super::{markdown_image, OutputTarget, TargetOptions};
+use crate::model::{Article, ImageName, Section};
+use std::io::{self, Write};
+
+/// Plain Markdown without any frontmatter, e.g. for archiving or a wiki.
+/// The date below the title is written in the format and timezone of the options.
+#[derive(Debug, Clone, Default)]
+pub struct Markdown {
+    pub options: TargetOptions,
+}
+
+impl Markdown {
+    pub fn new(options: TargetOptions) -> Self {
+        Markdown { options }
+    }
+}
+
+impl OutputTarget for Markdown {
+    fn options(&self) -> &TargetOptions {
+        &self.options
+    }
+
+    fn write_article(
+        &self,
+        output: &mut dyn Write,
+        article: &Article,
+        year: u32,
+        index: usize,
+    ) -> io::Result<()> {
+        writeln!(output, "# {}\n", article.title)?;
+        let date = self.options.format_date(article);
+        match &article.author {
+            Some(author) => writeln!(output, "*{}, {}*\n", date, author)?,
+            None => writeln!(output, "*{}*\n", date)?,
+        }
+        output.write_all(article.text.as_bytes())?;
+        for (image_index, image) in article.images.iter().enumerate() {
+            let path = ImageName::of(article, year, index, image_index).path();
+            markdown_image(output, image, &path, &self.options.strings)?;
+        }
+        Ok(())
+    }
+
+    fn section_index(&self, _section: &Section, _year: u32) -> Option<String> {
+        None
+    }
+}
@@ -0,0 +1,562 @@
+//! What the articles are written as. Every supported static site generator or format
+//! is a module implementing [`OutputTarget`], registered in [`TARGETS`].
+
+use crate::{
+    model::{Article, ArticleNumber, Image, ImageName, Section},
+    sentences,
+};
+use chrono::{DateTime, NaiveDateTime, TimeDelta, TimeZone};
+use chrono_tz::Tz;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+mod hugo;
+mod markdown;
+mod template;
+mod zola;
+
+pub use hugo::Hugo;
+pub use markdown::Markdown;
+pub use template::{Templates, GALLERY};
+pub use zola::Zola;
+
+lazy_static::lazy_static! {
+    // Shortcodes, images, HTML tags and comments like the read more divider.
+    static ref MARKUP_REGEX: Regex =
+        Regex::new(r"\{\{[<%].*?[%>]\}\}|!\[[^\]]*\]\([^)]*\)|<[^>]*>").unwrap();
+    static ref LINK_REGEX: Regex = Regex::new(r"\[([^\]]*)\]\([^)]*\)").unwrap();
+    // Headings, quotes, list items and tables at the start of a line.
+    static ref BLOCK_REGEX: Regex = Regex::new(r"(?m)^\s*([#>|]+|[-*+]|\d+\.)\s+").unwrap();
+    static ref EMPHASIS_REGEX: Regex = Regex::new(r"\*+|`+|\\").unwrap();
+}
+
+/// Default format of the article dates in the written files.
+pub const DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Default format of the article dates with a [`timezone`](TargetOptions::timezone), RFC 3339.
+pub const ZONED_DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%:z";
+
+/// What a target may be configured with, unset fields keep their defaults.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TargetOptions {
+    /// Format of the article dates in the written files, see [`chrono::format::strftime`].
+    /// With a [`timezone`](Self::timezone), the default is [`ZONED_DATE_FORMAT`].
+    pub date_format: String,
+    /// Timezone of the server of the old website, which the dates of the source are
+    /// local to, like `Europe/Berlin`. `None` writes them without an offset.
+    pub timezone: Option<Tz>,
+    /// Thumbnail of the articles without images.
+    pub default_thumbnail: String,
+    /// Directory of the site that the articles of the sections without a
+    /// [`dir`](Section::dir) are in, in their URLs, and that the thumbnails are in below
+    /// `img/`.
+    pub root: String,
+    pub frontmatter: FrontmatterKeys,
+    pub templates: Templates,
+    /// Extra fields of every frontmatter by their key, e.g. for the fields a theme
+    /// expects. They replace the built-in fields with the same key.
+    pub fields: BTreeMap<String, Field>,
+    /// What the description of the articles is made of.
+    pub description: Description,
+    /// The texts the target writes besides the articles.
+    pub strings: Strings,
+}
+
+impl Default for TargetOptions {
+    fn default() -> Self {
+        TargetOptions {
+            date_format: DATE_FORMAT.to_string(),
+            timezone: None,
+            default_thumbnail: "img/default.png".to_string(),
+            root: "einsaetze".to_string(),
+            frontmatter: FrontmatterKeys::default(),
+            templates: Templates::default(),
+            fields: BTreeMap::new(),
+            description: Description::default(),
+            strings: Strings::default(),
+        }
+    }
+}
+
+impl TargetOptions {
+    /// The format of the dates, which has the offset if there is a timezone.
+    pub fn date_format(&self) -> &str {
+        match self.timezone {
+            Some(_) if self.date_format == DATE_FORMAT => ZONED_DATE_FORMAT,
+            _ => &self.date_format,
+        }
+    }
+
+    /// The date of `article` in [`date_format`](Self::date_format), in the [`timezone`](Self::timezone).
+    pub fn format_date(&self, article: &Article) -> String {
+        match self.zoned_date(article.date) {
+            Some(date) => date.format(self.date_format()).to_string(),
+            None => article.date.format(self.date_format()).to_string(),
+        }
+    }
+
+    /// `date` of the source in the [`timezone`](Self::timezone), if there is one.
+    pub fn zoned_date(&self, date: NaiveDateTime) -> Option<DateTime<Tz>> {
+        let timezone = self.timezone?;
+        // A time skipped when the clocks go forward is taken in the offset before the change.
+        let hour = TimeDelta::hours(1);
+        let zoned = timezone.from_local_datetime(&date).earliest().or_else(|| {
+            let before = timezone.from_local_datetime(&(date - hour));
+            before.earliest().map(|date| date + hour)
+        });
+        Some(zoned.unwrap_or_else(|| timezone.from_utc_datetime(&date)))
+    }
+}
+
+/// What the description in the frontmatter of an article is made of, which search
+/// engines and the teasers of the archive show.
+///
+/// ```toml
+/// description = { sentences = 2 }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase", deny_unknown_fields)]
+pub enum Description {
+    #[default]
+    Title,
+    /// The first sentences of the text.
+    Sentences(usize),
+    /// The start of the text up to this many characters, cut after a word and ending
+    /// with `…`.
+    Characters(usize),
+}
+
+impl Description {
+    /// The description of `article`, its title if the text is empty.
+    pub fn of(self, article: &Article) -> String {
+        let text = match self {
+            Description::Title => return article.title.clone(),
+            Description::Sentences(count) => sentences::one_per_line(&plain_text(&article.text))
+                .lines()
+                .take(count)
+                .collect::<Vec<_>>()
+                .join(" "),
+            Description::Characters(count) => truncate(&plain_text(&article.text), count),
+        };
+        if text.is_empty() {
+            article.title.clone()
+        } else {
+            text
+        }
+    }
+}
+
+/// The words of the Markdown `text` on one line, without the images, links and
+/// formatting.
+fn plain_text(text: &str) -> String {
+    let text = MARKUP_REGEX.replace_all(text, " ");
+    let text = LINK_REGEX.replace_all(&text, "$1");
+    let text = BLOCK_REGEX.replace_all(&text, "");
+    let text = EMPHASIS_REGEX.replace_all(&text, "");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `text` cut after the last word that fits into `count` characters with the `…`.
+fn truncate(text: &str, count: usize) -> String {
+    if text.chars().count() <= count {
+        return text.to_string();
+    }
+    let end = text
+        .char_indices()
+        .nth(count.saturating_sub(1))
+        .map_or(text.len(), |(end, _)| end);
+    let head = &text[..end];
+    // A single word longer than the description is cut within.
+    let cut = if text[end..].starts_with(' ') {
+        head
+    } else {
+        head.rfind(' ').map_or(head, |space| &head[..space])
+    };
+    format!("{}…", cut.trim_end_matches([',', ';', ':', '-', ' ']))
+}
+
+/// The value of an extra frontmatter field, see [`TargetOptions::fields`].
+///
+/// ```toml
+/// type = "einsatz"
+/// weight = 10
+/// featured_image = "{{image}}"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Field {
+    Bool(bool),
+    Integer(i64),
+    /// A Handlebars template given the `id`, `title`, `date`, `year`, `number`, `slug`,
+    /// `section`, `author`, `tags`, `categories`, `thumbnail`, the path of the first
+    /// `image`, the `summary`, which is the first paragraph of the `text`, and the `text`.
+    /// Without any placeholders it is written as it is.
+    Template(String),
+    List(Vec<Field>),
+}
+
+impl Field {
+    /// Checks that the templates of the field are valid Handlebars.
+    pub fn check(&self) -> Result<(), handlebars::TemplateError> {
+        match self {
+            Field::Template(field) => handlebars::Template::compile(field).map(drop),
+            Field::List(fields) => fields.iter().try_for_each(Field::check),
+            Field::Bool(_) | Field::Integer(_) => Ok(()),
+        }
+    }
+
+    fn render(&self, data: &FieldData) -> io::Result<Value> {
+        Ok(match self {
+            Field::Bool(field) => Value::Bool(*field),
+            Field::Integer(field) => Value::from(*field),
+            Field::Template(field) => Value::String(template::render(field, data)?),
+            Field::List(fields) => Value::Array(
+                fields
+                    .iter()
+                    .map(|field| field.render(data))
+                    .collect::<io::Result<_>>()?,
+            ),
+        })
+    }
+}
+
+/// What the templates of the [`fields`](TargetOptions::fields) are given.
+#[derive(Debug, Serialize)]
+struct FieldData<'a> {
+    id: Option<u64>,
+    title: &'a str,
+    date: String,
+    year: u32,
+    number: String,
+    slug: Option<&'a str>,
+    section: Option<&'a str>,
+    author: Option<&'a str>,
+    tags: &'a [String],
+    categories: &'a [String],
+    thumbnail: String,
+    image: Option<String>,
+    summary: &'a str,
+    text: &'a str,
+}
+
+/// The [`fields`](TargetOptions::fields) of `article`, the `index`-th of `year`.
+fn fields(
+    article: &Article,
+    year: u32,
+    index: usize,
+    options: &TargetOptions,
+) -> io::Result<Map<String, Value>> {
+    if options.fields.is_empty() {
+        return Ok(Map::new());
+    }
+    let data = FieldData {
+        id: article.original_id,
+        title: &article.title,
+        date: options.format_date(article),
+        year,
+        number: ArticleNumber(index).to_string(),
+        slug: article.slug.as_deref(),
+        section: article.section.as_deref(),
+        author: article.author.as_deref(),
+        tags: &article.tags,
+        categories: &article.categories,
+        thumbnail: thumbnail(article, year, index, options),
+        image: (!article.images.is_empty()).then(|| ImageName::of(article, year, index, 0).path()),
+        summary: article
+            .text
+            .trim_start()
+            .split("\n\n")
+            .next()
+            .unwrap_or_default(),
+        text: &article.text,
+    };
+    options
+        .fields
+        .iter()
+        .map(|(key, field)| Ok((key.clone(), field.render(&data)?)))
+        .collect()
+}
+
+/// Names of the frontmatter fields, for themes that expect other names.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FrontmatterKeys {
+    pub title: String,
+    pub date: String,
+    /// See [`Article::draft`].
+    pub draft: String,
+    pub description: String,
+    pub thumbnail: String,
+    pub author: String,
+    pub tags: String,
+    pub categories: String,
+    /// See [`Article::aliases`].
+    pub aliases: String,
+    pub resources: String,
+    /// See [`Article::source_hash`].
+    pub source_hash: String,
+    /// See [`Article::converter_hash`].
+    pub converter_hash: String,
+}
+
+impl Default for FrontmatterKeys {
+    fn default() -> Self {
+        FrontmatterKeys {
+            title: "title".to_string(),
+            date: "date".to_string(),
+            draft: "draft".to_string(),
+            description: "description".to_string(),
+            thumbnail: "thumbnail".to_string(),
+            author: "author".to_string(),
+            tags: "tags".to_string(),
+            categories: "categories".to_string(),
+            aliases: "aliases".to_string(),
+            resources: "resources".to_string(),
+            source_hash: "source_hash".to_string(),
+            converter_hash: "converter_hash".to_string(),
+        }
+    }
+}
+
+/// The texts written besides the articles, in German by default, for sites in other
+/// languages, e.g. from a file like `strings/en.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Strings {
+    /// Title of the sections without a [title](Section::title) of their own.
+    pub section: String,
+    /// Title of the year indexes, with the `{section}` title and the `{year}`.
+    pub year_index: String,
+    /// Alt text of the images without one, for screen readers.
+    pub image_alt: Option<String>,
+    /// Labels of the fields of the [CMS configuration](OutputTarget::cms_config).
+    pub cms: CmsLabels,
+}
+
+impl Default for Strings {
+    fn default() -> Self {
+        Strings {
+            section: "Einsätze".to_string(),
+            year_index: "{section} {year}".to_string(),
+            image_alt: None,
+            cms: CmsLabels::default(),
+        }
+    }
+}
+
+impl Strings {
+    /// The title of `section`, its own or the default one.
+    pub fn section_title<'a>(&'a self, section: &'a Section) -> &'a str {
+        section.title.as_deref().unwrap_or(&self.section)
+    }
+
+    /// The title of the index of `year` in `section`.
+    pub fn year_title(&self, section: &Section, year: u32) -> String {
+        self.year_index
+            .replace("{section}", self.section_title(section))
+            .replace("{year}", &year.to_string())
+    }
+
+    /// The alt text of `image`, its own or the default one.
+    pub fn alt<'a>(&'a self, image: &'a Image) -> Option<&'a str> {
+        image.alt.as_deref().or(self.image_alt.as_deref())
+    }
+}
+
+/// The labels of the fields in the CMS, one per field of the frontmatter.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CmsLabels {
+    pub title: String,
+    pub date: String,
+    pub draft: String,
+    pub description: String,
+    pub thumbnail: String,
+    pub author: String,
+    pub tags: String,
+    pub categories: String,
+    pub images: String,
+    /// The name of an image, which the shortcodes refer to it by.
+    pub image_name: String,
+    pub image: String,
+    pub text: String,
+}
+
+impl Default for CmsLabels {
+    fn default() -> Self {
+        CmsLabels {
+            title: "Titel".to_string(),
+            date: "Datum".to_string(),
+            draft: "Entwurf".to_string(),
+            description: "Beschreibung".to_string(),
+            thumbnail: "Vorschaubild".to_string(),
+            author: "Autor".to_string(),
+            tags: "Tags".to_string(),
+            categories: "Kategorien".to_string(),
+            images: "Bilder".to_string(),
+            image_name: "Name".to_string(),
+            image: "Bild".to_string(),
+            text: "Text".to_string(),
+        }
+    }
+}
+
+/// Renders articles and section indexes for one static site generator.
+///
+/// Every article is written into its own directory, next to an `img` directory with
+/// its images, named as [`ImageName`].
+pub trait OutputTarget: Sync {
+    /// What the target was configured with.
+    fn options(&self) -> &TargetOptions;
+
+    /// Name of the article file within the article directory.
+    fn article_file_name(&self) -> &str {
+        "index.md"
+    }
+
+    /// Writes `article`, the `index`-th article of `year` in the section of
+    /// [`Article::section`].
+    fn write_article(
+        &self,
+        output: &mut dyn Write,
+        article: &Article,
+        year: u32,
+        index: usize,
+    ) -> io::Result<()>;
+
+    /// The [`Article::source_hash`] of `content`, an article file written by the target,
+    /// if the target keeps it. Without one, articles are compared by their content.
+    fn source_hash(&self, _content: &str) -> Option<String> {
+        None
+    }
+
+    /// The [`Article::converter_hash`] of `content` like its
+    /// [`source_hash`](Self::source_hash).
+    fn converter_hash(&self, _content: &str) -> Option<String> {
+        None
+    }
+
+    /// The `_index.md` of `year` in `section`, if the target uses one.
+    fn section_index(&self, section: &Section, year: u32) -> Option<String>;
+
+    /// The `_index.md` of the directory of `section` that holds its years, if the target
+    /// uses one, see [`output::write_scaffold`](crate::output::write_scaffold).
+    fn section_dir_index(&self, _section: &Section) -> Option<String> {
+        None
+    }
+
+    /// The archetype that new articles are created from, with the frontmatter of the
+    /// converted ones, if the target has them.
+    fn archetype(&self) -> Option<String> {
+        None
+    }
+
+    /// Path of `article`, the `index`-th article of `year`, in the built site, if the
+    /// target knows it.
+    fn article_url(&self, _article: &Article, _year: u32, _index: usize) -> Option<String> {
+        None
+    }
+
+    /// A link from the text of an article to the article at `path` in the content
+    /// directory, like `2019/0003`, if the target can link to the files of the content.
+    fn article_link(&self, _path: &str) -> Option<String> {
+        None
+    }
+
+    /// Site configuration the content of `sections` in `years` relies on, if the target
+    /// has one, to merge into the configuration of the site by hand.
+    fn site_config(&self, _sections: &[Section], _years: &[u32]) -> Option<String> {
+        None
+    }
+
+    /// Collections of a Git-based CMS like Decap for the content of `sections` in
+    /// `years`, if the target has them, to merge into the configuration of the CMS by hand.
+    fn cms_config(&self, _sections: &[Section], _years: &[u32]) -> Option<String> {
+        None
+    }
+}
+
+/// Name of the file of `article` in its directory, the
+/// [`article_file_name`](OutputTarget::article_file_name) of `target` with the language
+/// of the article before the extension, like `index.de.md`, as Hugo and Zola name
+/// translations.
+pub(crate) fn file_name(target: &dyn OutputTarget, article: &Article) -> String {
+    let file_name = target.article_file_name();
+    match (&article.language, file_name.rsplit_once('.')) {
+        (Some(language), Some((stem, extension))) => {
+            format!("{}.{}.{}", stem, language, extension)
+        }
+        (Some(language), None) => format!("{}.{}", file_name, language),
+        (None, _) => file_name.to_string(),
+    }
+}
+
+/// Path of the thumbnail of `article`, the `index`-th article of `year`, in the static
+/// files of the site or relative to the article if it is
+/// [bundled](Article::bundle_thumbnail), or the default thumbnail of `options`.
+fn thumbnail(article: &Article, year: u32, index: usize, options: &TargetOptions) -> String {
+    if article.images.is_empty() {
+        return options.default_thumbnail.clone();
+    }
+    if article.bundle_thumbnail {
+        return article.bundle_thumbnail_name();
+    }
+    format!(
+        "img/{}/{}{}/{}.{}",
+        options.root,
+        article
+            .section
+            .as_ref()
+            .map(|dir| format!("{}/", dir))
+            .unwrap_or_default(),
+        year,
+        article.thumbnail_name(index),
+        article.format_of_image(0).extension()
+    )
+}
+
+/// Writes `image` at `path` as a Markdown image in a paragraph of its own, with the
+/// caption as its title and the alt text of `strings`.
+fn markdown_image(
+    output: &mut dyn Write,
+    image: &Image,
+    path: &str,
+    strings: &Strings,
+) -> io::Result<()> {
+    let alt = strings.alt(image).unwrap_or_default();
+    match &image.caption {
+        Some(caption) => writeln!(
+            output,
+            "\n![{}]({} \"{}\")",
+            alt,
+            path,
+            caption.replace('"', "\\\"")
+        ),
+        None => writeln!(output, "\n![{}]({})", alt, path),
+    }
+}
+
+type TargetConstructor = fn(&TargetOptions) -> Box<dyn OutputTarget>;
+
+/// All known targets by name.
+pub const TARGETS: &[(&str, TargetConstructor)] = &[
+    ("hugo", |options| Box::new(Hugo::new(options.clone()))),
+    ("markdown", |options| {
+        Box::new(Markdown::new(options.clone()))
+    }),
+    ("zola", |options| Box::new(Zola::new(options.clone()))),
+];
+
+/// Creates the target registered as `name`.
+pub fn create(name: &str, options: &TargetOptions) -> Option<Box<dyn OutputTarget>> {
+    TARGETS
+        .iter()
+        .find(|(target_name, _)| *target_name == name)
+        .map(|(_, constructor)| constructor(options))
+}
@@ -0,0 +1,66 @@
+//! Templates of the users for parts of the articles, rendered with Handlebars, see
+//! [`Templates`].
+
+use handlebars::{handlebars_helper, no_escape, Handlebars, TemplateError};
+use serde::{Deserialize, Serialize};
+use std::io;
+
+lazy_static::lazy_static! {
+    // Nothing is HTML escaped, the output is Markdown. `quote` makes a value a double
+    // quoted string, as Hugo shortcodes, YAML and TOML take it.
+    static ref HANDLEBARS: Handlebars<'static> = {
+        handlebars_helper!(quote: |value: Json| {
+            serde_json::to_string(value).expect("JSON values serialize")
+        });
+        let mut handlebars = Handlebars::new();
+        handlebars.register_escape_fn(no_escape);
+        handlebars.register_helper("quote", Box::new(quote));
+        handlebars
+    };
+}
+
+/// Handlebars templates replacing parts of what the [`Hugo`](super::Hugo) target writes,
+/// e.g. for the shortcodes of a theme. The unset ones keep the built-in output.
+///
+/// ```toml
+/// [target_options.templates]
+/// image = '\{{< figure src={{quote src}}{{#if caption}} caption={{quote caption}}{{/if}} >}}'
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Templates {
+    /// The frontmatter with its delimiters, given `title`, `date`, `draft`, `description`,
+    /// `thumbnail`, `author`, `tags`, `categories`, `aliases`, `resources` with a `name`
+    /// and `src` each, `source_hash`, `converter_hash` and the extra `fields`, see
+    /// [`TargetOptions::fields`](super::TargetOptions::fields).
+    pub frontmatter: Option<String>,
+    /// The shortcode of an image, given its resource `name`, `src`, `alt` and `caption`.
+    pub image: Option<String>,
+    /// The shortcode of all images of an article with more than one, written after its
+    /// first image instead of the others, e.g. [`GALLERY`]. Given the `dir` of the images
+    /// and the `images` as the image template is given them.
+    pub gallery: Option<String>,
+}
+
+/// A gallery of the `gallery` shortcode of Hugo themes like hugo-shortcode-gallery.
+pub const GALLERY: &str = r#"\{{< gallery dir="{{dir}}" >}}"#;
+
+impl Templates {
+    /// Checks that the templates are valid Handlebars.
+    pub fn check(&self) -> Result<(), TemplateError> {
+        for template in [&self.frontmatter, &self.image, &self.gallery]
+            .into_iter()
+            .flatten()
+        {
+            handlebars::Template::compile(template)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders `template` with `data`.
+pub(super) fn render(template: &str, data: &impl Serialize) -> io::Result<String> {
+    HANDLEBARS
+        .render_template(template, data)
+        .map_err(io::Error::other)
+}
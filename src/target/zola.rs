@@ -0,0 +1,203 @@
+use super::{fields, markdown_image, thumbnail, OutputTarget, TargetOptions, ZONED_DATE_FORMAT};
+use crate::model::{slugify, Article, ImageName, Section};
+use serde::Serialize;
+use std::io::{self, Write};
+use toml::{value::Datetime, Table, Value};
+
+/// Zola pages with a TOML frontmatter. The images are colocated assets of the page,
+/// linked as Markdown images, so no shortcode of a theme is needed.
+///
+/// Zola has fixed fields for the title, date and description and takes dates in TOML
+/// only, so the [`date_format`](TargetOptions::date_format) and the keys of these fields
+/// are not used. The dates have an offset with a [`timezone`](TargetOptions::timezone).
+/// The thumbnail, source hash and the extra [`fields`](TargetOptions::fields) go into
+/// `[extra]`.
+#[derive(Debug, Clone, Default)]
+pub struct Zola {
+    pub options: TargetOptions,
+}
+
+impl Zola {
+    pub fn new(options: TargetOptions) -> Self {
+        Zola { options }
+    }
+}
+
+/// The frontmatter of an article, the tables last as TOML needs them.
+#[derive(Debug, Serialize)]
+struct Frontmatter {
+    title: String,
+    description: String,
+    date: Datetime,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    draft: bool,
+    /// Keeps the URLs of Hugo `/<root>/:year/:slug/`, Zola would take the directory.
+    path: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    authors: Vec<String>,
+    /// Zola writes a page per alias, which cannot have a query.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<String>,
+    #[serde(skip_serializing_if = "Table::is_empty")]
+    taxonomies: Table,
+    extra: Table,
+}
+
+/// The frontmatter of a section index, its pages sorted by date.
+#[derive(Debug, Serialize)]
+struct IndexFrontmatter<'a> {
+    title: &'a str,
+    sort_by: &'a str,
+}
+
+impl IndexFrontmatter<'_> {
+    fn to_index(&self) -> String {
+        let toml = toml::to_string(self).expect("The index frontmatter always serializes");
+        format!("+++\n{}+++\n", toml)
+    }
+}
+
+/// Path of `article` of `year` in the built site, without the slashes around it, below
+/// the [`root`](TargetOptions::root) of `options` if its section has no directory.
+fn path(article: &Article, year: u32, options: &TargetOptions) -> String {
+    format!(
+        "{}/{}/{}",
+        article.section.as_deref().unwrap_or(&options.root),
+        year,
+        slugify(&article.title)
+    )
+}
+
+/// The string at `key` in the `[extra]` of the TOML frontmatter of `content`, an article
+/// file.
+fn extra_string(content: &str, key: &str) -> Option<String> {
+    let frontmatter = content.strip_prefix("+++\n")?.split("\n+++\n").next()?;
+    let fields: Table = toml::from_str(frontmatter).ok()?;
+    Some(fields.get("extra")?.get(key)?.as_str()?.to_string())
+}
+
+impl OutputTarget for Zola {
+    fn options(&self) -> &TargetOptions {
+        &self.options
+    }
+
+    fn write_article(
+        &self,
+        output: &mut dyn Write,
+        article: &Article,
+        year: u32,
+        index: usize,
+    ) -> io::Result<()> {
+        let options = &self.options;
+        let date = match options.zoned_date(article.date) {
+            Some(date) => date.format(ZONED_DATE_FORMAT).to_string(),
+            None => article.date.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        };
+        let date = date.parse().map_err(io::Error::other)?;
+        let mut taxonomies = Table::new();
+        if !article.tags.is_empty() {
+            taxonomies.insert("tags".to_string(), article.tags.clone().into());
+        }
+        if !article.categories.is_empty() {
+            taxonomies.insert("categories".to_string(), article.categories.clone().into());
+        }
+        let mut extra = Table::new();
+        extra.insert(
+            options.frontmatter.thumbnail.clone(),
+            thumbnail(article, year, index, options).into(),
+        );
+        if let Some(hash) = &article.source_hash {
+            extra.insert(options.frontmatter.source_hash.clone(), hash.clone().into());
+        }
+        if let Some(hash) = &article.converter_hash {
+            extra.insert(
+                options.frontmatter.converter_hash.clone(),
+                hash.clone().into(),
+            );
+        }
+        for (key, value) in fields(article, year, index, options)? {
+            extra.insert(key, Value::try_from(value).map_err(io::Error::other)?);
+        }
+        let frontmatter = Frontmatter {
+            title: article.title.clone(),
+            description: options.description.of(article),
+            date,
+            draft: article.draft,
+            path: path(article, year, options),
+            authors: article.author.iter().cloned().collect(),
+            aliases: article
+                .aliases
+                .iter()
+                .filter(|alias| !alias.contains('?'))
+                .cloned()
+                .collect(),
+            taxonomies,
+            extra,
+        };
+        writeln!(output, "+++")?;
+        output.write_all(
+            toml::to_string(&frontmatter)
+                .map_err(io::Error::other)?
+                .as_bytes(),
+        )?;
+        writeln!(output, "+++\n")?;
+        output.write_all(article.text.as_bytes())?;
+        for (image_index, image) in article.images.iter().enumerate() {
+            let path = ImageName::of(article, year, index, image_index).path();
+            markdown_image(output, image, &path, &self.options.strings)?;
+        }
+        Ok(())
+    }
+
+    fn source_hash(&self, content: &str) -> Option<String> {
+        extra_string(content, &self.options.frontmatter.source_hash)
+    }
+
+    fn converter_hash(&self, content: &str) -> Option<String> {
+        extra_string(content, &self.options.frontmatter.converter_hash)
+    }
+
+    fn section_index(&self, section: &Section, year: u32) -> Option<String> {
+        let title = self.options.strings.year_title(section, year);
+        Some(
+            IndexFrontmatter {
+                title: &title,
+                sort_by: "date",
+            }
+            .to_index(),
+        )
+    }
+
+    fn section_dir_index(&self, section: &Section) -> Option<String> {
+        Some(
+            IndexFrontmatter {
+                title: self.options.strings.section_title(section),
+                sort_by: "date",
+            }
+            .to_index(),
+        )
+    }
+
+    fn article_url(&self, article: &Article, year: u32, _index: usize) -> Option<String> {
+        Some(format!("/{}/", path(article, year, &self.options)))
+    }
+
+    /// Zola links to the file of the page, it checks the link when it builds the site.
+    fn article_link(&self, path: &str) -> Option<String> {
+        Some(format!("@/{}/{}", path, self.article_file_name()))
+    }
+
+    fn site_config(&self, _sections: &[Section], _years: &[u32]) -> Option<String> {
+        Some(
+            concat!(
+                "# Merge into the config.toml of the site, before its first table.\n\n",
+                "taxonomies = [\n",
+                "  { name = \"einsatzarten\" },\n",
+                "  { name = \"tags\" },\n",
+                "  { name = \"categories\" },\n",
+                "]\n",
+            )
+            .to_string(),
+        )
+    }
+}
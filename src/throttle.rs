@@ -0,0 +1,75 @@
+//! Bandwidth limiting for reading the images of the old website, see `--io-limit`.
+
+use std::{
+    fs::File,
+    io::{self, Read, Write},
+    path::Path,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Limits the combined throughput of all copies, reads and downloads going through it,
+/// so that a conversion does not saturate the storage or the server the old website
+/// lives on.
+pub struct Throttle {
+    bytes_per_second: f64,
+    next_free: Mutex<Instant>,
+}
+
+impl Throttle {
+    pub fn new(mebibytes_per_second: f64) -> Self {
+        Throttle {
+            bytes_per_second: mebibytes_per_second * 1024.0 * 1024.0,
+            next_free: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Reserves the bandwidth for `bytes` and returns how long to wait until it is
+    /// available, for the downloads, which must not block their runtime.
+    pub fn reserve(&self, bytes: usize) -> Duration {
+        let now = Instant::now();
+        let mut next_free = self.next_free.lock().unwrap();
+        let start = (*next_free).max(now);
+        *next_free = start + Duration::from_secs_f64(bytes as f64 / self.bytes_per_second);
+        start - now
+    }
+
+    /// Reserves the bandwidth for `bytes` and blocks until it is available.
+    fn consume(&self, bytes: usize) {
+        thread::sleep(self.reserve(bytes));
+    }
+
+    /// Reads the whole file at `source`, like [`fs::read`](std::fs::read).
+    pub fn read(&self, source: &Path) -> io::Result<Vec<u8>> {
+        let mut reader = File::open(source)?;
+        let mut content = Vec::new();
+        let mut buffer = vec![0; CHUNK_SIZE];
+        loop {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                return Ok(content);
+            }
+            self.consume(read);
+            content.extend_from_slice(&buffer[..read]);
+        }
+    }
+
+    pub fn copy(&self, source: &Path, destination: &Path) -> io::Result<u64> {
+        let mut reader = File::open(source)?;
+        let mut writer = File::create(destination)?;
+        let mut buffer = vec![0; CHUNK_SIZE];
+        let mut copied = 0;
+        loop {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                return Ok(copied);
+            }
+            self.consume(read);
+            writer.write_all(&buffer[..read])?;
+            copied += read as u64;
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Timing breakdown of a conversion, see `--timings`.
+
+use std::{
+    fmt::Write as _,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Phase {
+    Parse,
+    Filter,
+    Clean,
+    Markdown,
+    Images,
+    Thumbnails,
+}
+
+impl Phase {
+    const ALL: [Phase; 6] = [
+        Phase::Parse,
+        Phase::Filter,
+        Phase::Clean,
+        Phase::Markdown,
+        Phase::Images,
+        Phase::Thumbnails,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Phase::Parse => "parse",
+            Phase::Filter => "filter",
+            Phase::Clean => "clean",
+            Phase::Markdown => "markdown",
+            Phase::Images => "image copy",
+            Phase::Thumbnails => "thumbnails",
+        }
+    }
+}
+
+/// Time spent per phase of a conversion, summed over all worker threads,
+/// plus the duration of every single article and image.
+#[derive(Default)]
+pub struct Timings {
+    phases: [AtomicU64; Phase::ALL.len()],
+    articles: Mutex<Vec<(Duration, String)>>,
+    images: Mutex<Vec<(Duration, String)>>,
+}
+
+impl Timings {
+    pub fn add(&self, phase: Phase, duration: Duration) {
+        self.phases[phase as usize].fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub fn time<T>(&self, phase: Phase, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.add(phase, start.elapsed());
+        result
+    }
+
+    pub fn record_article(&self, label: String, duration: Duration) {
+        self.articles.lock().unwrap().push((duration, label));
+    }
+
+    pub fn record_image(&self, label: String, duration: Duration) {
+        self.add(Phase::Images, duration);
+        self.images.lock().unwrap().push((duration, label));
+    }
+
+    /// Number of articles written.
+    pub fn article_count(&self) -> usize {
+        self.articles.lock().unwrap().len()
+    }
+
+    /// Number of images written.
+    pub fn image_count(&self) -> usize {
+        self.images.lock().unwrap().len()
+    }
+
+    /// The wall time `total`, the time per phase and the `slowest` articles and images,
+    /// one per line.
+    pub fn report(&self, total: Duration, slowest: usize) -> String {
+        let mut report = String::new();
+        writeln!(report, "Total wall time: {}", format_duration(total))
+            .expect("Formatting into a string cannot fail");
+        writeln!(report, "Time per phase (summed over all threads):")
+            .expect("Formatting into a string cannot fail");
+        for phase in Phase::ALL {
+            let nanos = self.phases[phase as usize].load(Ordering::Relaxed);
+            writeln!(
+                report,
+                "  {:<12}{:>12}",
+                phase.name(),
+                format_duration(Duration::from_nanos(nanos))
+            )
+            .expect("Formatting into a string cannot fail");
+        }
+        write_slowest(
+            &mut report,
+            "articles",
+            &self.articles.lock().unwrap(),
+            slowest,
+        );
+        write_slowest(&mut report, "images", &self.images.lock().unwrap(), slowest);
+        report
+    }
+}
+
+fn write_slowest(report: &mut String, kind: &str, entries: &[(Duration, String)], slowest: usize) {
+    if entries.is_empty() {
+        return;
+    }
+    let mut entries: Vec<&(Duration, String)> = entries.iter().collect();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.0));
+    writeln!(report, "Slowest {}:", kind).expect("Formatting into a string cannot fail");
+    for (duration, label) in entries.into_iter().take(slowest) {
+        writeln!(report, "  {:>12}  {}", format_duration(*duration), label)
+            .expect("Formatting into a string cannot fail");
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.3}s", duration.as_secs_f64())
+}
@@ -0,0 +1,212 @@
+//! The cleanup turning the HTML of the old website into Markdown text.
+
+use crate::{html, plugins::Plugins, sentences};
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::{
+    borrow::Cow,
+    fs, io,
+    path::{Path, PathBuf},
+    thread,
+};
+
+/// A single cleanup step applied to the text of an article.
+#[derive(Debug, Clone)]
+pub enum Transform {
+    /// Replaces every match of the regex. The replacement may reference
+    /// capture groups like `${1}`.
+    Regex { regex: Regex, replacement: String },
+    /// Replaces every occurrence of a literal string.
+    Literal {
+        pattern: String,
+        replacement: String,
+    },
+    /// Converts the HTML into Markdown, see [`html::to_markdown`].
+    HtmlToMarkdown,
+    /// Translates the tags of Joomla plugins into shortcodes, see [`Plugins`].
+    Plugins(Plugins),
+    /// Puts every sentence on its own line, see [`sentences::one_per_line`].
+    SentencePerLine,
+}
+
+impl Transform {
+    pub fn regex(pattern: &str, replacement: &str) -> Result<Self, regex::Error> {
+        Ok(Transform::Regex {
+            regex: Regex::new(pattern)?,
+            replacement: replacement.to_string(),
+        })
+    }
+
+    pub fn literal(pattern: &str, replacement: &str) -> Self {
+        Transform::Literal {
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+        }
+    }
+
+    /// Only allocates if the transform actually changes the text.
+    fn apply<'a>(&self, text: Cow<'a, str>) -> Cow<'a, str> {
+        match self {
+            Transform::Regex { regex, replacement } => {
+                let replaced = match regex.replace_all(&text, replacement.as_str()) {
+                    Cow::Borrowed(_) => None,
+                    Cow::Owned(replaced) => Some(replaced),
+                };
+                replaced.map_or(text, Cow::Owned)
+            }
+            Transform::Literal {
+                pattern,
+                replacement,
+            } => {
+                if text.contains(pattern.as_str()) {
+                    Cow::Owned(text.replace(pattern.as_str(), replacement))
+                } else {
+                    text
+                }
+            }
+            Transform::HtmlToMarkdown => Cow::Owned(html::to_markdown(&text)),
+            Transform::Plugins(plugins) => match plugins.translate(&text) {
+                Cow::Borrowed(_) => text,
+                Cow::Owned(translated) => Cow::Owned(translated),
+            },
+            Transform::SentencePerLine => {
+                let split = sentences::one_per_line(&text);
+                if split == text {
+                    text
+                } else {
+                    Cow::Owned(split)
+                }
+            }
+        }
+    }
+
+    fn describe(&self) -> (&'static str, &str, &str) {
+        match self {
+            Transform::Regex { regex, replacement } => ("regex", regex.as_str(), replacement),
+            Transform::Literal {
+                pattern,
+                replacement,
+            } => ("literal", pattern, replacement),
+            Transform::HtmlToMarkdown => ("html-to-markdown", "", ""),
+            // The templates are fed in by the fingerprint.
+            Transform::Plugins(_) => ("plugins", "", ""),
+            Transform::SentencePerLine => ("sentence-per-line", "", ""),
+        }
+    }
+}
+
+/// An ordered list of transforms, compiled once and applied to every article.
+#[derive(Debug, Clone, Default)]
+pub struct Pipeline {
+    transforms: Vec<Transform>,
+}
+
+impl Pipeline {
+    /// The cleanup every conversion starts with:
+    /// HTML is converted into Markdown and the text is reformatted to one sentence per line.
+    pub fn builtin() -> Self {
+        let mut pipeline = Pipeline::markdown();
+        pipeline.push(Transform::SentencePerLine);
+        pipeline
+    }
+
+    /// The cleanup keeping the lines of the text: HTML is converted into Markdown and the
+    /// tags of the Joomla plugins are translated with the default [`Plugins`].
+    pub fn markdown() -> Self {
+        let mut pipeline = Pipeline::default();
+        pipeline.push(Transform::HtmlToMarkdown);
+        pipeline.push(Transform::Plugins(Plugins::default()));
+        pipeline
+    }
+
+    /// Translates the tags of Joomla plugins with `plugins` instead of the ones the
+    /// pipeline has, or after all other transforms if it has none.
+    pub fn plugins(&mut self, plugins: Plugins) -> &mut Self {
+        let existing = self
+            .transforms
+            .iter_mut()
+            .find(|transform| matches!(transform, Transform::Plugins(_)));
+        match existing {
+            Some(transform) => *transform = Transform::Plugins(plugins),
+            None => self.transforms.push(Transform::Plugins(plugins)),
+        }
+        self
+    }
+
+    pub fn push(&mut self, transform: Transform) -> &mut Self {
+        self.transforms.push(transform);
+        self
+    }
+
+    pub fn apply<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        self.transforms
+            .iter()
+            .fold(Cow::Borrowed(text), |text, transform| transform.apply(text))
+    }
+
+    /// Feeds every transform in order into `update`, so that caches can tell
+    /// two pipelines apart.
+    pub fn fingerprint(&self, mut update: impl FnMut(&[u8])) {
+        for transform in &self.transforms {
+            let (kind, pattern, replacement) = transform.describe();
+            for part in [kind, pattern, replacement] {
+                update(part.as_bytes());
+                update(&[0]);
+            }
+            if let Transform::Plugins(plugins) = transform {
+                for (name, template) in plugins.templates() {
+                    for part in [name, template] {
+                        update(part.as_bytes());
+                        update(&[0]);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Cleaned texts from earlier runs, keyed by the hash of the pipeline and the source text.
+/// The cache is only an optimization, failing to read or write it is not an error.
+#[derive(Debug, Clone)]
+pub struct TextCache {
+    dir: PathBuf,
+}
+
+impl TextCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        TextCache { dir: dir.into() }
+    }
+
+    /// Looks the cleaned text up in the cache before running the pipeline.
+    pub fn clean(&self, pipeline: &Pipeline, text: &str) -> String {
+        let cache_path = self.dir.join(format!("{}.md", Self::key(pipeline, text)));
+        if let Ok(cleaned) = fs::read_to_string(&cache_path) {
+            return cleaned;
+        }
+
+        let cleaned = pipeline.apply(text).into_owned();
+        let _ = Self::write_entry(&cache_path, &cleaned);
+        cleaned
+    }
+
+    /// The key covers the version and the cleanup pipeline as well, so that changing the
+    /// cleanup invalidates the cached texts instead of serving stale ones.
+    fn key(pipeline: &Pipeline, text: &str) -> String {
+        let mut hasher = Sha256::new();
+        // Another version may clean the texts differently.
+        hasher.update(env!("CARGO_PKG_VERSION"));
+        hasher.update([0]);
+        pipeline.fingerprint(|part| hasher.update(part));
+        hasher.update(text);
+        format!("{:x}", hasher.finalize())
+    }
+
+    fn write_entry(cache_path: &Path, text: &str) -> io::Result<()> {
+        let cache_dir = cache_path.parent().expect("Cache entry without directory");
+        fs::create_dir_all(cache_dir)?;
+        // Write to a temporary file first so a concurrent reader never sees a partial entry.
+        let temporary_path = cache_path.with_extension(format!("{:?}.tmp", thread::current().id()));
+        fs::write(&temporary_path, text)?;
+        fs::rename(&temporary_path, cache_path)
+    }
+}
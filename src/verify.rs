@@ -0,0 +1,144 @@
+//! Building the site with Hugo after a conversion, to catch broken frontmatter and
+//! shortcodes before publishing.
+
+use crate::{output::SITE_CONFIG_FILE, report::Report};
+use std::{fmt, fs, io, path::Path, process::Command};
+
+/// Layouts of the scaffolded site, by their path. The image shortcode fails the build
+/// for page resources that do not exist instead of rendering anything.
+const LAYOUTS: &[(&str, &str)] = &[
+    ("_default/single.html", "{{ .Content }}\n"),
+    (
+        "_default/list.html",
+        "{{ range .Pages }}<a href=\"{{ .RelPermalink }}\">{{ .Title }}</a>{{ end }}\n",
+    ),
+    (
+        "shortcodes/image.html",
+        concat!(
+            "{{ with .Page.Resources.GetMatch (.Get \"src\") }}{{ .RelPermalink }}",
+            "{{ else }}{{ errorf \"%s: no image %q\" .Position (.Get \"src\") }}{{ end }}\n",
+        ),
+    ),
+    (
+        "shortcodes/gallery.html",
+        "{{ range .Page.Resources.ByType \"image\" }}{{ .RelPermalink }}{{ end }}\n",
+    ),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Level::Error => "error",
+            Level::Warning => "warning",
+        })
+    }
+}
+
+/// An error or warning of a Hugo build.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildIssue {
+    pub level: Level,
+    pub message: String,
+}
+
+impl fmt::Display for BuildIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.level, self.message)
+    }
+}
+
+/// Builds the Hugo site in `site_dir` with the `hugo` executable, into `destination`
+/// so that the site's own `public` directory stays untouched.
+pub fn hugo_build(hugo: &Path, site_dir: &Path, destination: &Path) -> io::Result<Vec<BuildIssue>> {
+    let output = Command::new(hugo)
+        .arg("--quiet")
+        .arg("--source")
+        .arg(site_dir)
+        .arg("--destination")
+        .arg(destination)
+        .output()?;
+    let mut issues: Vec<BuildIssue> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .chain(String::from_utf8_lossy(&output.stderr).lines())
+        .filter_map(parse_issue)
+        .collect();
+    if !output.status.success() && !issues.iter().any(|issue| issue.level == Level::Error) {
+        issues.push(BuildIssue {
+            level: Level::Error,
+            message: format!("Hugo failed with {}", output.status),
+        });
+    }
+    Ok(issues)
+}
+
+/// Adds the `issues` of a Hugo build to the [`build_issues`](crate::report::ArticleReport::build_issues)
+/// of the articles of `report` they are about, as told by the path of the article file in
+/// the message. The others are about no article.
+pub fn attach_issues(report: &mut Report, issues: &[BuildIssue]) {
+    // Hugo names the files by their full path, which ends with the content directory and
+    // the path of the file in it.
+    let messages: Vec<String> = issues
+        .iter()
+        .map(|issue| issue.message.replace('\\', "/"))
+        .collect();
+    for article in &mut report.articles {
+        let Some(file) = &article.file else {
+            continue;
+        };
+        let file = format!("content/{}", file.to_string_lossy().replace('\\', "/"));
+        for (issue, message) in issues.iter().zip(&messages) {
+            if message.contains(&file) {
+                article.build_issues.push(issue.to_string());
+            }
+        }
+    }
+}
+
+/// Writes a minimal Hugo site into `site_dir` that builds the content of `output_dir`
+/// with the [`SITE_CONFIG_FILE`] of the conversion and stand-ins for the layouts and
+/// shortcodes of a theme, for checking the output without the actual site.
+pub fn scaffold(output_dir: &Path, site_dir: &Path) -> io::Result<()> {
+    let content_dir = fs::canonicalize(output_dir)?.join("content");
+    let mut config = format!(
+        "baseURL = \"http://localhost/\"\ncontentDir = {}\n",
+        toml::Value::String(content_dir.to_string_lossy().into_owned())
+    );
+    // The site config of the target only has tables, which have to come last.
+    match fs::read_to_string(output_dir.join(SITE_CONFIG_FILE)) {
+        Ok(site_config) => config.push_str(&format!("\n{}", site_config)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+        Err(error) => return Err(error),
+    }
+    fs::create_dir_all(site_dir)?;
+    fs::write(site_dir.join("hugo.toml"), config)?;
+    for (path, layout) in LAYOUTS {
+        let path = site_dir.join("layouts").join(path);
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, layout)?;
+    }
+    Ok(())
+}
+
+/// Hugo starts its log lines with the level, e.g. `ERROR 2024/05/01 12:00:00 ...` or
+/// `WARN  ...`, and the final message with `Error: `.
+fn parse_issue(line: &str) -> Option<BuildIssue> {
+    let (level, message) = if let Some(message) = line.strip_prefix("ERROR") {
+        (Level::Error, message)
+    } else if let Some(message) = line.strip_prefix("Error:") {
+        (Level::Error, message)
+    } else if let Some(message) = line.strip_prefix("WARN") {
+        (Level::Warning, message)
+    } else {
+        return None;
+    };
+    Some(BuildIssue {
+        level,
+        message: message.trim().to_string(),
+    })
+}
@@ -0,0 +1,44 @@
+//! JavaScript bindings to preview the conversion of a single article in the browser,
+//! see `wasm/index.html`.
+
+use crate::{
+    converter::READ_MORE,
+    render::{self, RenderOptions},
+    source::JoomlaRow,
+    target::{self, TargetOptions},
+    transform::Pipeline,
+};
+use std::path::Path;
+use wasm_bindgen::prelude::*;
+
+/// Converts `row`, one entry of the `data` array of a Joomla export as JSON, and
+/// returns the article file as the target registered as `target` writes it.
+#[wasm_bindgen]
+pub fn preview(row: &str, target: &str) -> Result<String, JsError> {
+    let row: JoomlaRow = serde_json::from_str(row)?;
+    let source = row
+        .into_source_article(0, Path::new("preview"))?
+        .ok_or_else(|| JsError::new("The entry has no creation date or category"))?;
+    let target = target::create(target, &TargetOptions::default())
+        .ok_or_else(|| JsError::new(&format!("Unknown target {}", target)))?;
+    let rendered = render::convert_article(
+        &source,
+        &RenderOptions {
+            target: &*target,
+            pipeline: &Pipeline::builtin(),
+            hooks: &[],
+            section: None,
+            read_more: Some(READ_MORE),
+            image_format: Default::default(),
+            image_names: None,
+            bundle_thumbnails: false,
+            index: 0,
+            dir: None,
+            old_url: None,
+            multilingual: false,
+            attachments: false,
+            article_paths: None,
+        },
+    )?;
+    Ok(rendered.content)
+}
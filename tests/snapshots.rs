@@ -0,0 +1,1973 @@
+//! Snapshots of the rendered articles of `tests/fixtures/missions.json`, for every target,
+//! and of the tree a whole conversion writes.
+//!
+//! Review changed snapshots with `cargo insta review`, or accept all of them at once
+//! with `cargo update-snapshots`.
+
+use ff_website_converter::{
+    calendar,
+    conflict::{Conflict, Resolution},
+    html,
+    images::ImageCache,
+    mapping,
+    model::{Article, ArticleKey, ArticleNumber, Section},
+    output::{self, Existing, MissingImages, YearArticles, CHECKPOINT_FILE, STAGING_DIR},
+    plugins::{self, Plugins},
+    redirects,
+    render::{self, RenderOptions, RenderedArticle},
+    sentences,
+    source::{ArticleSource, JoomlaJson, Merged, SourceArticle, WordPress},
+    stats::{InputStats, Stats},
+    target::{
+        Description, Field, Hugo, Markdown, OutputTarget, TargetOptions, Templates, Zola, GALLERY,
+        TARGETS,
+    },
+    throttle::Throttle,
+    transform::Pipeline,
+    verify, Converter, ConverterBuilder,
+};
+
+use chrono::NaiveDate;
+use sha2::{Digest, Sha256};
+use std::{
+    cell::Cell,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/missions.json");
+const CATID: u32 = 5;
+
+/// The mission articles of the fixture with their year and number, as the converter
+/// numbers them.
+fn fixture_articles() -> Vec<(u32, usize, SourceArticle)> {
+    let mut articles = Vec::new();
+    JoomlaJson::new(FIXTURE)
+        .stream(&mut |article| {
+            if article.catid == Some(CATID) && article.state.is_converted(false) {
+                articles.push(article);
+            }
+        })
+        .unwrap();
+
+    let keys: Vec<ArticleKey> = articles.iter().map(ArticleKey::new).collect();
+    let mut years: Vec<u32> = keys.iter().map(|key| key.year).collect();
+    years.sort_unstable();
+    years.dedup();
+
+    let mut numbered = Vec::new();
+    for year in years {
+        let year_articles = YearArticles::new(&keys, &Section::new(CATID), year);
+        for (index, key) in year_articles.articles.iter().enumerate() {
+            let article = articles
+                .iter()
+                .find(|article| article.ordinal == key.ordinal)
+                .unwrap();
+            numbered.push((year, index, article.clone()));
+        }
+    }
+    numbered
+}
+
+/// An old website in `dir` with the `images` of the articles, each holding its own name.
+fn old_website(dir: &Path, images: &[&str]) -> PathBuf {
+    let old_website_dir = dir.join("old");
+    fs::create_dir_all(&old_website_dir).unwrap();
+    for image in images {
+        let path = old_website_dir.join("images/einsaetze").join(image);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, image).unwrap();
+    }
+    old_website_dir
+}
+
+#[test]
+fn articles() {
+    let pipeline = Pipeline::builtin();
+    for (name, constructor) in TARGETS {
+        let target = constructor(&TargetOptions::default());
+        for (year, index, source) in fixture_articles() {
+            let options = RenderOptions {
+                target: &*target,
+                pipeline: &pipeline,
+                hooks: &[],
+                section: None,
+                read_more: Some("<!--more-->"),
+                image_format: Default::default(),
+                image_names: None,
+                bundle_thumbnails: false,
+                index,
+                dir: None,
+                old_url: None,
+                multilingual: false,
+                attachments: false,
+                article_paths: None,
+            };
+            let rendered = render::convert_article(&source, &options).unwrap();
+            insta::assert_snapshot!(
+                format!("{}-{}-{}", name, year, ArticleNumber(index)),
+                rendered.content
+            );
+        }
+    }
+}
+
+#[test]
+fn images() {
+    let pipeline = Pipeline::builtin();
+    let target = TARGETS[0].1(&TargetOptions::default());
+    let images: Vec<_> = fixture_articles()
+        .into_iter()
+        .flat_map(|(_, index, source)| {
+            let options = RenderOptions {
+                target: &*target,
+                pipeline: &pipeline,
+                hooks: &[],
+                section: None,
+                read_more: Some("<!--more-->"),
+                image_format: Default::default(),
+                image_names: None,
+                bundle_thumbnails: false,
+                index,
+                dir: None,
+                old_url: None,
+                multilingual: false,
+                attachments: false,
+                article_paths: None,
+            };
+            render::convert_article(&source, &options).unwrap().images
+        })
+        .collect();
+    insta::assert_debug_snapshot!(images);
+}
+
+/// The images of messy exports, with backslashes, encoded umlauts and absolute paths.
+#[test]
+fn image_paths() {
+    let images = html::images(concat!(
+        r#"<img src="images\einsaetze\2021\brand.jpg">"#,
+        r#"<img src="images/einsa%CC%88tze/%C3%BCbung%201.jpg?v=2">"#,
+        r#"<img src="/images/./fahrzeuge/../hlf.png#top">"#,
+        r#"<img src="//old-site.example/images/logo.png">"#,
+        r#"<img src="https://old-site.example/images/M%C3%A4rz.jpg">"#,
+    ));
+    let sources: Vec<_> = images.into_iter().map(|image| image.source).collect();
+    insta::assert_debug_snapshot!(sources);
+}
+
+/// The characters an author wrote as entities, which stay text instead of becoming HTML
+/// or formatting.
+#[test]
+fn escaped_text() {
+    assert_eq!(
+        html::to_markdown("<p>Mit &lt;b&gt;fett&lt;/b&gt; und &#42;Sternen&#42;</p>").trim(),
+        r"Mit \<b>fett\</b> und \*Sternen\*"
+    );
+}
+
+/// The images named after a pattern, the same in the frontmatter and in the output.
+#[test]
+fn image_names() {
+    let dir = tempfile::tempdir().unwrap();
+    let old_website_dir = old_website(dir.path(), &["kueche.jpg"]);
+    let converter = Converter::builder()
+        .input(FIXTURE)
+        .old_website_dir(&old_website_dir)
+        .output_dir(dir.path().join("output"))
+        .cache_dir(dir.path().join("cache"))
+        .years(vec![2019])
+        .image_names("{date}-{slug}-{image}.{ext}")
+        .build()
+        .unwrap();
+    assert!(converter.convert().unwrap().failures.is_empty());
+    insta::assert_snapshot!(tree(&dir.path().join("output/content/2019/0001")));
+
+    let builder = || {
+        Converter::builder()
+            .input(FIXTURE)
+            .old_website_dir(&old_website_dir)
+    };
+    assert_eq!(
+        builder()
+            .image_names("{title}-{image}.{ext}")
+            .build()
+            .err()
+            .map(|error| error.to_string()),
+        Some(
+            "Unknown placeholder {title} in the image names, use {year}, {article}, {dir}, \
+             {image}, {slug}, {date}, {ext}"
+                .to_string()
+        )
+    );
+    assert_eq!(
+        builder()
+            .image_names("{slug}.{ext}")
+            .build()
+            .err()
+            .map(|error| error.to_string()),
+        Some("The image names {slug}.{ext} need {image} and {ext}".to_string())
+    );
+}
+
+#[test]
+fn slug_dirs() {
+    let mut articles = Vec::new();
+    JoomlaJson::new(FIXTURE)
+        .stream(&mut |article| {
+            if article.catid == Some(CATID) && article.state.is_converted(false) {
+                articles.push(article);
+            }
+        })
+        .unwrap();
+    let keys: Vec<ArticleKey> = articles.iter().map(ArticleKey::new).collect();
+    let mut year_articles = YearArticles::new(&keys, &Section::new(CATID), 2019);
+    year_articles.name_by_slug();
+    let dirs: Vec<_> = (0..year_articles.articles.len())
+        .map(|index| year_articles.dir(index).unwrap().to_string())
+        .collect();
+
+    let source = articles
+        .iter()
+        .find(|article| article.ordinal == year_articles.articles[1].ordinal)
+        .unwrap();
+    let rendered = render::convert_article(
+        source,
+        &RenderOptions {
+            target: &Hugo::default(),
+            pipeline: &Pipeline::builtin(),
+            hooks: &[],
+            section: None,
+            read_more: Some("<!--more-->"),
+            image_format: Default::default(),
+            image_names: None,
+            bundle_thumbnails: false,
+            index: 1,
+            dir: Some(&dirs[1]),
+            old_url: None,
+            multilingual: false,
+            attachments: false,
+            article_paths: None,
+        },
+    )
+    .unwrap();
+    insta::assert_debug_snapshot!((dirs, rendered.path, rendered.images));
+}
+
+/// The directories named after the IDs stay when an article is added before the others.
+#[test]
+fn id_dirs() {
+    let mut keys: Vec<ArticleKey> = fixture_articles()
+        .iter()
+        .map(|(_, _, article)| ArticleKey::new(article))
+        .collect();
+    let dirs = |keys: &[ArticleKey]| {
+        let mut year_articles = YearArticles::new(keys, &Section::new(CATID), 2019);
+        year_articles.name_by_id();
+        (0..year_articles.articles.len())
+            .map(|index| year_articles.dir(index).unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    let before = dirs(&keys);
+
+    let mut backdated = keys[0].clone();
+    backdated.ordinal = keys.len();
+    backdated.id = Some(999);
+    backdated.date = "2019-01-01T00:00:00".parse().unwrap();
+    keys.push(backdated);
+    let mut without_id = keys[0].clone();
+    without_id.ordinal = keys.len();
+    without_id.id = None;
+    keys.push(without_id);
+    let after = dirs(&keys);
+    assert_eq!(after[0], "999");
+    assert!(before.iter().all(|dir| after.contains(dir)));
+    insta::assert_debug_snapshot!(after);
+}
+
+#[test]
+fn templates() {
+    let target = Hugo::new(TargetOptions {
+        templates: Templates {
+            frontmatter: Some(
+                "+++\ntitle = {{quote title}}\n{{#each resources}}# {{name}}: {{src}}\n{{/each}}+++\n"
+                    .to_string(),
+            ),
+            image: Some(
+                r#"\{{< figure src={{quote src}}{{#if alt}} alt={{quote alt}}{{/if}}{{#if caption}} caption={{quote caption}}{{/if}} >}}"#
+                    .to_string(),
+            ),
+            gallery: None,
+        },
+        ..TargetOptions::default()
+    });
+    let (_, index, source) = fixture_articles()
+        .into_iter()
+        .find(|(_, _, source)| source.html.contains("<figcaption>"))
+        .unwrap();
+    let rendered = render::convert_article(
+        &source,
+        &RenderOptions {
+            target: &target,
+            pipeline: &Pipeline::builtin(),
+            hooks: &[],
+            section: None,
+            read_more: Some("<!--more-->"),
+            image_format: Default::default(),
+            image_names: None,
+            bundle_thumbnails: false,
+            index,
+            dir: None,
+            old_url: None,
+            multilingual: false,
+            attachments: false,
+            article_paths: None,
+        },
+    )
+    .unwrap();
+    insta::assert_snapshot!(rendered.content);
+}
+
+/// The images of an article after the first as a gallery.
+#[test]
+fn gallery() {
+    let target = Hugo::new(TargetOptions {
+        templates: Templates {
+            gallery: Some(GALLERY.to_string()),
+            ..Templates::default()
+        },
+        ..TargetOptions::default()
+    });
+    let (_, index, source) = fixture_articles()
+        .into_iter()
+        .max_by_key(|(_, _, source)| source.html.matches("<img").count())
+        .unwrap();
+    let rendered = render::convert_article(
+        &source,
+        &RenderOptions {
+            target: &target,
+            pipeline: &Pipeline::builtin(),
+            hooks: &[],
+            section: None,
+            read_more: Some("<!--more-->"),
+            image_format: Default::default(),
+            image_names: None,
+            bundle_thumbnails: false,
+            index,
+            dir: None,
+            old_url: None,
+            multilingual: false,
+            attachments: false,
+            article_paths: None,
+        },
+    )
+    .unwrap();
+    insta::assert_snapshot!(rendered.content);
+}
+
+/// The texts of `strings/en.toml` in the indexes and the CMS configuration, and the
+/// default alt text of the images without one.
+#[test]
+fn strings() {
+    let converter = Converter::builder()
+        .input(FIXTURE)
+        .old_website_dir("tests")
+        .strings("strings/en.toml")
+        .build()
+        .unwrap();
+    let target = &converter.target;
+    insta::assert_snapshot!(
+        "strings-indexes",
+        [
+            target.section_index(&Section::new(CATID), 2019).unwrap(),
+            target.section_dir_index(&sections()[1]).unwrap(),
+            target.section_index(&sections()[1], 2019).unwrap(),
+        ]
+        .join("")
+    );
+    insta::assert_snapshot!(
+        "strings-cms",
+        target.cms_config(&[Section::new(CATID)], &[2019]).unwrap()
+    );
+
+    let mut options = TargetOptions::default();
+    options.strings.image_alt = Some("Einsatzfoto".to_string());
+    let (_, index, source) = fixture_articles()
+        .into_iter()
+        .max_by_key(|(_, _, source)| source.html.matches("<img").count())
+        .unwrap();
+    for (name, constructor) in TARGETS {
+        let target = constructor(&options);
+        let rendered = render::convert_article(
+            &source,
+            &RenderOptions {
+                target: &*target,
+                pipeline: &Pipeline::builtin(),
+                hooks: &[],
+                section: None,
+                read_more: Some("<!--more-->"),
+                image_format: Default::default(),
+                image_names: None,
+                bundle_thumbnails: false,
+                index,
+                dir: None,
+                old_url: None,
+                multilingual: false,
+                attachments: false,
+                article_paths: None,
+            },
+        )
+        .unwrap();
+        insta::assert_snapshot!(format!("{}-image-alt", name), rendered.content);
+    }
+}
+
+/// Extra frontmatter fields of a theme, in YAML and in the `[extra]` of Zola.
+#[test]
+fn fields() {
+    let options = TargetOptions {
+        fields: [
+            ("type", Field::Template("einsatz".to_string())),
+            ("weight", Field::Integer(10)),
+            ("featured_image", Field::Template("{{image}}".to_string())),
+            ("summary", Field::Template("{{summary}}".to_string())),
+            (
+                "keywords",
+                Field::List(vec![
+                    Field::Template("{{id}}".to_string()),
+                    Field::Template("{{year}}".to_string()),
+                ]),
+            ),
+            ("title", Field::Template("{{number}} {{title}}".to_string())),
+        ]
+        .into_iter()
+        .map(|(key, field)| (key.to_string(), field))
+        .collect(),
+        ..TargetOptions::default()
+    };
+    let (_, index, source) = fixture_articles()
+        .into_iter()
+        .find(|(_, _, source)| source.html.contains("<img"))
+        .unwrap();
+    let hugo = Hugo::new(options.clone());
+    let zola = Zola::new(options);
+    for (name, target) in [("hugo", &hugo as &dyn OutputTarget), ("zola", &zola)] {
+        let rendered = render::convert_article(
+            &source,
+            &RenderOptions {
+                target,
+                pipeline: &Pipeline::builtin(),
+                hooks: &[],
+                section: None,
+                read_more: Some("<!--more-->"),
+                image_format: Default::default(),
+                image_names: None,
+                bundle_thumbnails: false,
+                index,
+                dir: None,
+                old_url: None,
+                multilingual: false,
+                attachments: false,
+                article_paths: None,
+            },
+        )
+        .unwrap();
+        insta::assert_snapshot!(format!("fields-{}", name), rendered.content);
+    }
+}
+
+/// The descriptions made of the text, quoted where YAML and TOML need it.
+#[test]
+fn descriptions() {
+    let mut descriptions = Vec::new();
+    for description in [Description::Sentences(2), Description::Characters(40)] {
+        let options = TargetOptions {
+            description,
+            ..TargetOptions::default()
+        };
+        let targets: [Box<dyn OutputTarget>; 2] = [
+            Box::new(Hugo::new(options.clone())),
+            Box::new(Zola::new(options)),
+        ];
+        for target in &targets {
+            for (_, index, source) in fixture_articles() {
+                let options = RenderOptions {
+                    target: &**target,
+                    pipeline: &Pipeline::builtin(),
+                    hooks: &[],
+                    section: None,
+                    read_more: Some("<!--more-->"),
+                    image_format: Default::default(),
+                    image_names: None,
+                    bundle_thumbnails: false,
+                    index,
+                    dir: None,
+                    old_url: None,
+                    multilingual: false,
+                    attachments: false,
+                    article_paths: None,
+                };
+                let content = render::convert_article(&source, &options).unwrap().content;
+                let line = content
+                    .lines()
+                    .find(|line| line.starts_with("description"))
+                    .unwrap();
+                descriptions.push(line.to_string());
+            }
+        }
+    }
+    insta::assert_debug_snapshot!(descriptions);
+}
+
+#[test]
+fn timezone() {
+    let options = TargetOptions {
+        timezone: Some("Europe/Berlin".parse().unwrap()),
+        ..TargetOptions::default()
+    };
+    let targets: [Box<dyn OutputTarget>; 3] = [
+        Box::new(Hugo::new(options.clone())),
+        Box::new(Markdown::new(options.clone())),
+        Box::new(Zola::new(options)),
+    ];
+    let mut dates = Vec::new();
+    for target in &targets {
+        for (_, index, source) in fixture_articles() {
+            let options = RenderOptions {
+                target: &**target,
+                pipeline: &Pipeline::builtin(),
+                hooks: &[],
+                section: None,
+                read_more: None,
+                image_format: Default::default(),
+                image_names: None,
+                bundle_thumbnails: false,
+                index,
+                dir: None,
+                old_url: None,
+                multilingual: false,
+                attachments: false,
+                article_paths: None,
+            };
+            let content = render::convert_article(&source, &options).unwrap().content;
+            let date = content
+                .lines()
+                // The Markdown target writes the date in italics below the title.
+                .find(|line| line.starts_with("date") || line.starts_with('*'))
+                .unwrap();
+            dates.push(date.to_string());
+        }
+    }
+    insta::assert_debug_snapshot!(dates);
+}
+
+/// The URLs and thumbnails of the articles of the section without a directory, below
+/// another root than `einsaetze`.
+#[test]
+fn root() {
+    let options = TargetOptions {
+        root: "berichte".to_string(),
+        ..TargetOptions::default()
+    };
+    let targets: [Box<dyn OutputTarget>; 2] = [
+        Box::new(Hugo::new(options.clone())),
+        Box::new(Zola::new(options)),
+    ];
+    let (_, index, source) = fixture_articles()
+        .into_iter()
+        .find(|(_, _, source)| source.html.contains("<img"))
+        .unwrap();
+    let mut paths = Vec::new();
+    for target in &targets {
+        let options = RenderOptions {
+            target: &**target,
+            pipeline: &Pipeline::builtin(),
+            hooks: &[],
+            section: None,
+            read_more: None,
+            image_format: Default::default(),
+            image_names: None,
+            bundle_thumbnails: false,
+            index,
+            dir: None,
+            old_url: None,
+            multilingual: false,
+            attachments: false,
+            article_paths: None,
+        };
+        let rendered = render::convert_article(&source, &options).unwrap();
+        let thumbnail = rendered
+            .content
+            .lines()
+            .find(|line| line.starts_with("thumbnail"))
+            .unwrap();
+        paths.push(thumbnail.to_string());
+        paths.extend(target.article_url(&rendered.article, rendered.year, rendered.index));
+    }
+    let site_config = targets[0]
+        .site_config(&[Section::new(CATID)], &[2019])
+        .unwrap();
+    paths.extend(
+        site_config
+            .lines()
+            .filter(|line| line.contains(":slug"))
+            .map(str::to_string),
+    );
+    insta::assert_debug_snapshot!(paths);
+}
+
+/// The processed images of an earlier run, copied from the cache while neither the image
+/// nor the settings change.
+#[test]
+fn image_cache() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = ImageCache::new(dir.path().join("cache"));
+    let source = dir.path().join("kueche.jpg");
+    let destination = dir.path().join("0001-00.webp");
+    fs::write(&source, "kueche").unwrap();
+    let processed = Cell::new(0);
+    let write = |settings: &str| {
+        let entry = cache.entry(&fs::read(&source).unwrap(), settings, &destination);
+        assert_eq!(entry.extension().unwrap(), "webp");
+        cache
+            .write(&entry, &destination, || {
+                processed.set(processed.get() + 1);
+                fs::write(
+                    &destination,
+                    format!("{} {}", settings, fs::read_to_string(&source)?),
+                )?;
+                Ok(fs::metadata(&destination)?.len())
+            })
+            .unwrap();
+        processed.get()
+    };
+    assert_eq!(write("WebP 80"), 1);
+    fs::remove_file(&destination).unwrap();
+    assert_eq!(write("WebP 80"), 1);
+    assert_eq!(fs::read_to_string(&destination).unwrap(), "WebP 80 kueche");
+    assert_eq!(write("WebP 60"), 2);
+    fs::write(&source, "kueche aufgehellt").unwrap();
+    assert_eq!(write("WebP 80"), 3);
+    assert_eq!(
+        fs::read_to_string(&destination).unwrap(),
+        "WebP 80 kueche aufgehellt"
+    );
+}
+
+/// The reads and downloads sharing the throughput of the throttle, what comes after a
+/// MiB at 1 MiB/s waits for about a second.
+#[test]
+fn throttle() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("kueche.jpg");
+    fs::write(&source, "kueche").unwrap();
+    let throttle = Throttle::new(1.0);
+    assert_eq!(throttle.read(&source).unwrap(), b"kueche");
+    throttle.reserve(1024 * 1024);
+    let wait = throttle.reserve(1);
+    assert!(wait > Duration::from_millis(900) && wait <= Duration::from_secs(1));
+}
+
+/// The date formats the dates cannot be written in, refused before the conversion.
+#[test]
+fn date_format() {
+    let dir = tempfile::tempdir().unwrap();
+    let error = |date_format: &str, timezone: Option<&str>| {
+        Converter::builder()
+            .input(FIXTURE)
+            .old_website_dir(dir.path())
+            .target_options(TargetOptions {
+                date_format: date_format.to_string(),
+                timezone: timezone.map(|timezone| timezone.parse().unwrap()),
+                ..TargetOptions::default()
+            })
+            .build()
+            .err()
+            .map(|error| error.to_string())
+    };
+    assert_eq!(
+        error("%d.%m.%Y %Q", None).as_deref(),
+        Some("Invalid date format %d.%m.%Y %Q")
+    );
+    assert_eq!(
+        error("%d.%m.%Y %:z", None).as_deref(),
+        Some("The date format %d.%m.%Y %:z has an offset, which needs a timezone")
+    );
+    assert_eq!(error("%d.%m.%Y %:z", Some("Europe/Berlin")), None);
+    assert_eq!(error("%d.%m.%Y", None), None);
+}
+
+#[test]
+fn sentences() {
+    let text = concat!(
+        "Alarm um 3.15 Uhr am 3. Mai. Ca. 20 Personen, z. B. Herr A. Müller, ",
+        "waren mit dem Lkw. vor Ort. Wer kam? Die Polizei!\n",
+        "\n",
+        "- Ein Satz. Noch einer (im Einsatz.) Und \"zitiert.\" Ende...\n",
+        "  - Verschachtelt. Auch hier.\n",
+        "\n",
+        "> Zitat eins. Zitat zwei.\n",
+        "\n",
+        "| Fahrzeug. | Besatzung. Stärke |\n",
+    );
+    insta::assert_snapshot!(sentences::one_per_line(text));
+}
+
+/// The tags of Joomla plugins as shortcodes, those of unknown plugins are kept.
+#[test]
+fn plugins() {
+    let html = concat!(
+        "<p>Bilder vom Einsatz:</p><p>{gallery}einsaetze/2019/05{/gallery}</p>",
+        "<p>{youtube}dQw4w9_WgXcQ{/youtube} {VIMEO}76979871{/VIMEO}</p>",
+        "<p>{loadposition kontakt}Kontakt: {mosmap width='500'|address='Hauptstraße 1'}</p>",
+    );
+    let mut pipeline = Pipeline::builtin();
+    let mut custom = Plugins::default();
+    custom.insert("loadposition", "");
+    pipeline.plugins(custom);
+    let text = pipeline.apply(html);
+    insta::assert_snapshot!(text);
+    assert_eq!(plugins::unknown_tags(&text), ["mosmap"]);
+}
+
+/// Embedded YouTube and Vimeo players as shortcodes, other iframes are dropped.
+#[test]
+fn iframes() {
+    let html = concat!(
+        "<p>Das Video vom Einsatz:</p>",
+        r#"<p><iframe width="560" height="315" src="https://www.youtube-nocookie.com/embed/dQw4w9_WgXcQ?rel=0" frameborder="0" allowfullscreen></iframe></p>"#,
+        r#"<ul><li>Übung: <iframe src="//player.vimeo.com/video/76979871"></iframe></li></ul>"#,
+        r#"<p>Anfahrt: <iframe src="https://www.google.com/maps/embed?pb=1"></iframe></p>"#,
+    );
+    insta::assert_snapshot!(Pipeline::builtin().apply(html));
+}
+
+/// What the Markdown of a table, a heading, styles and a map does not keep.
+#[test]
+fn losses() {
+    let html = concat!(
+        r#"<h2 style="color: red">Einsatzbericht</h2>"#,
+        "<table><tr><th>Fahrzeug</th><th>Besatzung</th></tr>",
+        "<tr><td>HLF 20</td><td>1/8</td></tr></table>",
+        r#"<p><span style="font-size: 14pt">Anfahrt</span>: "#,
+        r#"<iframe src="https://www.google.com/maps/embed?pb=1"></iframe></p>"#,
+        r#"<p><span>Video</span>: <iframe src="https://www.youtube.com/embed/dQw4w9_WgXcQ"></iframe></p>"#,
+        "<h2>Nachtrag</h2><script>track();</script>",
+    );
+    let losses: Vec<String> = html::losses(html)
+        .iter()
+        .map(|loss| loss.to_string())
+        .collect();
+    insta::assert_debug_snapshot!(losses);
+}
+
+#[test]
+fn names() {
+    let converter = Converter {
+        source: Box::new(JoomlaJson::new(FIXTURE)),
+        years: vec![2019, 2020],
+        authors: [(62, "Max Mustermann".to_string())].into(),
+        categories: [(CATID, "Einsätze".to_string())].into(),
+        ..Converter::default()
+    };
+    let names: Vec<_> = converter
+        .preview()
+        .unwrap()
+        .into_iter()
+        .map(|(_, rendered)| {
+            let article = rendered.article;
+            (
+                article.title,
+                article.author,
+                article.tags,
+                article.categories,
+            )
+        })
+        .collect();
+    insta::assert_debug_snapshot!(names);
+}
+
+#[test]
+fn drafts() {
+    let converter = Converter {
+        source: Box::new(JoomlaJson::new(FIXTURE)),
+        years: vec![2019, 2020],
+        drafts: true,
+        ..Converter::default()
+    };
+    let previews = converter.preview().unwrap();
+    assert!(previews
+        .iter()
+        .all(|(source, _)| source.title != "Testeintrag"));
+    let (_, rendered) = previews
+        .into_iter()
+        .find(|(_, rendered)| rendered.article.draft)
+        .unwrap();
+    insta::assert_snapshot!(rendered.content);
+}
+
+#[test]
+fn wordpress() {
+    let mut articles = Vec::new();
+    WordPress::new(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/blog.xml"
+    ))
+    .stream(&mut |article| articles.push(article))
+    .unwrap();
+    insta::assert_debug_snapshot!("wordpress-articles", articles);
+
+    let rendered = render::convert_article(
+        &articles[0],
+        &RenderOptions {
+            target: &Hugo::default(),
+            pipeline: &Pipeline::builtin(),
+            hooks: &[],
+            section: None,
+            read_more: Some("<!--more-->"),
+            image_format: Default::default(),
+            image_names: None,
+            bundle_thumbnails: false,
+            index: 0,
+            dir: None,
+            old_url: None,
+            multilingual: false,
+            attachments: false,
+            article_paths: None,
+        },
+    )
+    .unwrap();
+    insta::assert_snapshot!("wordpress-hugo", rendered.content);
+}
+
+/// A whole conversion of the fixture into a temporary directory.
+#[test]
+fn conversion() {
+    let dir = tempfile::tempdir().unwrap();
+    let old_website_dir = old_website(
+        dir.path(),
+        &["kueche.jpg", "vu-1.jpg", "vu-2.jpg", "vu-3.jpg"],
+    );
+    let converter = Converter {
+        source: Box::new(JoomlaJson::new(FIXTURE)),
+        old_website_dir,
+        output_dir: dir.path().join("output"),
+        cache_dir: dir.path().join("cache"),
+        years: vec![2019, 2020],
+        ..Converter::default()
+    };
+    let conversion = converter.convert().unwrap();
+    assert!(conversion.failures.is_empty());
+    insta::assert_snapshot!(tree(&converter.output_dir));
+}
+
+/// The cleaned texts of an earlier run left alone without the cache, even when they are stale.
+#[test]
+fn no_cache() {
+    let dir = tempfile::tempdir().unwrap();
+    let converter = |output: &str, cache: bool| Converter {
+        source: Box::new(JoomlaJson::new(FIXTURE)),
+        old_website_dir: old_website(dir.path(), &[]),
+        output_dir: dir.path().join(output),
+        cache_dir: dir.path().join("cache"),
+        cache,
+        years: vec![2019],
+        missing_images: MissingImages::Skip,
+        ..Converter::default()
+    };
+    converter("output", true).convert().unwrap();
+    let entries: Vec<PathBuf> = fs::read_dir(dir.path().join("cache/text"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert!(!entries.is_empty());
+    for entry in &entries {
+        fs::write(entry, "stale").unwrap();
+    }
+
+    converter("uncached", false).convert().unwrap();
+    assert_eq!(
+        tree(&dir.path().join("output")),
+        tree(&dir.path().join("uncached"))
+    );
+    for entry in &entries {
+        assert_eq!(fs::read_to_string(entry).unwrap(), "stale");
+    }
+}
+
+/// The thumbnails written into the article directories, without a thumbnail tree even
+/// when the years are staged.
+#[test]
+fn bundle_thumbnails() {
+    let dir = tempfile::tempdir().unwrap();
+    let old_website_dir = old_website(
+        dir.path(),
+        &["kueche.jpg", "vu-1.jpg", "vu-2.jpg", "vu-3.jpg"],
+    );
+    let converter = Converter {
+        source: Box::new(JoomlaJson::new(FIXTURE)),
+        old_website_dir,
+        output_dir: dir.path().join("output"),
+        cache_dir: dir.path().join("cache"),
+        years: vec![2019, 2020],
+        bundle_thumbnails: true,
+        staging: true,
+        ..Converter::default()
+    };
+    let conversion = converter.convert().unwrap();
+    assert!(conversion.failures.is_empty());
+    assert!(!converter.output_dir.join("thumbnail").exists());
+    insta::assert_snapshot!(tree(&converter.output_dir));
+}
+
+/// The image of an article named `.jpg` whatever its format, written with the extension of
+/// the format its first bytes tell, or with its own if they tell none.
+#[test]
+fn image_formats() {
+    let headers: [(&str, &[u8]); 6] = [
+        ("jpeg", b"\xFF\xD8\xFF\xE0\0\x10JFIF\0"),
+        ("png", b"\x89PNG\r\n\x1A\n\0\0\0\x0DIHDR"),
+        ("gif", b"GIF89a\x01\0\x01\0\0\0"),
+        ("webp", b"RIFF\x24\0\0\0WEBPVP8 "),
+        ("avif", b"\0\0\0\x1CftypavifMA1B"),
+        ("unknown", b"kueche"),
+    ];
+    for (name, header) in headers {
+        let dir = tempfile::tempdir().unwrap();
+        let old_website_dir = old_website(dir.path(), &[]);
+        let image = old_website_dir.join("images/einsaetze/kueche.jpg");
+        fs::create_dir_all(image.parent().unwrap()).unwrap();
+        fs::write(&image, header).unwrap();
+        let converter = Converter {
+            source: Box::new(JoomlaJson::new(FIXTURE)),
+            old_website_dir,
+            output_dir: dir.path().join("output"),
+            cache_dir: dir.path().join("cache"),
+            years: vec![2019],
+            ..Converter::default()
+        };
+        assert!(converter.convert().unwrap().failures.is_empty());
+        let output_dir = dir.path().join("output");
+        let article = fs::read_to_string(output_dir.join("content/2019/0001/index.md")).unwrap();
+        let references: Vec<&str> = article
+            .lines()
+            .filter(|line| line.starts_with("thumbnail:") || line.trim().starts_with("src:"))
+            .collect();
+        let files: Vec<String> = tree(&output_dir)
+            .lines()
+            .filter(|line| line.starts_with("== ") && !line.ends_with(".md"))
+            .filter(|line| line.contains("/0001"))
+            .map(str::to_string)
+            .collect();
+        insta::assert_snapshot!(
+            format!("image_formats-{}", name),
+            format!("{}\n{}", references.join("\n"), files.join("\n"))
+        );
+    }
+}
+
+/// A second run in every mode but asking, after an article was edited, another one and an
+/// image were deleted and an article was added by hand, telling which files it wrote,
+/// left alone or removed.
+#[test]
+fn existing() {
+    let mut outcomes = Vec::new();
+    for existing in [
+        Existing::SkipYears,
+        Existing::Overwrite,
+        Existing::SkipExisting,
+        Existing::UpdateChanged,
+    ] {
+        let dir = tempfile::tempdir().unwrap();
+        let old_website_dir = old_website(
+            dir.path(),
+            &["kueche.jpg", "vu-1.jpg", "vu-2.jpg", "vu-3.jpg"],
+        );
+        let converter = |existing| Converter {
+            source: Box::new(JoomlaJson::new(FIXTURE)),
+            old_website_dir: old_website_dir.clone(),
+            output_dir: dir.path().join("output"),
+            cache_dir: dir.path().join("cache"),
+            years: vec![2019, 2020],
+            existing,
+            ..Converter::default()
+        };
+        converter(Existing::SkipYears).convert().unwrap();
+        let content_dir = dir.path().join("output/content");
+        let edited = content_dir.join("2019/0000/index.md");
+        let deleted = content_dir.join("2020/0001/index.md");
+        let image = content_dir.join("2020/0000/img/2020-0000-01.jpg");
+        let added = content_dir.join("2020/0002/index.md");
+        let written = fs::read_to_string(&edited).unwrap();
+        fs::write(&edited, format!("{}Nachtrag\n", written)).unwrap();
+        fs::remove_file(&deleted).unwrap();
+        fs::remove_file(&image).unwrap();
+        fs::create_dir_all(added.parent().unwrap()).unwrap();
+        fs::write(&added, "---\ntitle: Nachgetragen\n---\n").unwrap();
+
+        let conversion = converter(existing).convert().unwrap();
+        assert!(conversion.failures.is_empty());
+        let edited = match fs::read_to_string(&edited).unwrap() {
+            content if content == written => "written",
+            _ => "left",
+        };
+        let state = |path: &Path, there: &'static str, missing: &'static str| {
+            if path.exists() {
+                there
+            } else {
+                missing
+            }
+        };
+        outcomes.push(format!(
+            "{:?}: edited {}, deleted {}, image {}, added {}",
+            existing,
+            edited,
+            state(&deleted, "written", "missing"),
+            state(&image, "written", "missing"),
+            state(&added, "left", "removed"),
+        ));
+    }
+    insta::assert_snapshot!(outcomes.join("\n"));
+}
+
+/// A second run asking about the articles edited since the first, keeping one and
+/// overwriting the other. The unchanged articles are not asked about.
+#[test]
+fn ask() {
+    let dir = tempfile::tempdir().unwrap();
+    let old_website_dir = old_website(
+        dir.path(),
+        &["kueche.jpg", "vu-1.jpg", "vu-2.jpg", "vu-3.jpg"],
+    );
+    let converter = |existing| Converter {
+        source: Box::new(JoomlaJson::new(FIXTURE)),
+        old_website_dir: old_website_dir.clone(),
+        output_dir: dir.path().join("output"),
+        cache_dir: dir.path().join("cache"),
+        years: vec![2019, 2020],
+        existing,
+        ..Converter::default()
+    };
+    converter(Existing::SkipYears).convert().unwrap();
+    let content_dir = dir.path().join("output/content");
+    let kept = content_dir.join("2019/0000/index.md");
+    let overwritten = content_dir.join("2020/0001/index.md");
+    let written = fs::read_to_string(&overwritten).unwrap();
+    let edited = fs::read_to_string(&kept)
+        .unwrap()
+        .replace("Motorsäge zerkleinert", "Kettensäge zerteilt");
+    fs::write(&kept, &edited).unwrap();
+    fs::write(&overwritten, format!("{}Nachtrag\n", written)).unwrap();
+
+    let conflicts = Arc::new(Mutex::new(Vec::new()));
+    let mut converter = converter(Existing::Ask);
+    converter.conflicts = Some(Box::new({
+        let conflicts = conflicts.clone();
+        move |conflict: &Conflict| {
+            let path = conflict.path.strip_prefix(&content_dir).unwrap();
+            conflicts
+                .lock()
+                .unwrap()
+                .push(format!("== {}\n{}", path.display(), conflict.diff()));
+            if path.starts_with("2020") {
+                Resolution::Overwrite
+            } else {
+                Resolution::Keep
+            }
+        }
+    }));
+    assert!(converter.convert().unwrap().failures.is_empty());
+    assert_eq!(fs::read_to_string(&kept).unwrap(), edited);
+    assert_eq!(fs::read_to_string(&overwritten).unwrap(), written);
+    let mut conflicts = conflicts.lock().unwrap().clone();
+    conflicts.sort();
+    insta::assert_snapshot!(conflicts.join(""));
+}
+
+/// A conversion with the hashes of the sources updated with other settings, which only
+/// writes the article changed in the source again, not the one edited by hand.
+#[test]
+fn converter_hash() {
+    let dir = tempfile::tempdir().unwrap();
+    let old_website_dir = old_website(
+        dir.path(),
+        &["kueche.jpg", "vu-1.jpg", "vu-2.jpg", "vu-3.jpg"],
+    );
+    let input = dir.path().join("missions.json");
+    fs::copy(FIXTURE, &input).unwrap();
+    let converter = |old_url: Option<&str>| Converter {
+        source: Box::new(JoomlaJson::new(&input)),
+        old_website_dir: old_website_dir.clone(),
+        output_dir: dir.path().join("output"),
+        cache_dir: dir.path().join("cache"),
+        years: vec![2019],
+        existing: Existing::UpdateChanged,
+        converter_hash: true,
+        old_url: old_url.map(str::to_string),
+        ..Converter::default()
+    };
+    converter(None).convert().unwrap();
+    let content_dir = dir.path().join("output/content/2019");
+    let edited = content_dir.join("0000/index.md");
+    let changed = content_dir.join("0001/index.md");
+    let text = fs::read_to_string(&edited)
+        .unwrap()
+        .replace("Motorsäge zerkleinert", "Kettensäge zerteilt");
+    fs::write(&edited, &text).unwrap();
+    let source = fs::read_to_string(&input).unwrap();
+    fs::write(&input, source.replace("Ca. 20 Kräfte", "Ca. 25 Kräfte")).unwrap();
+
+    let old_url = "/einsaetze/{catid}-einsaetze/{id}-{alias}.html";
+    assert!(converter(Some(old_url))
+        .convert()
+        .unwrap()
+        .failures
+        .is_empty());
+    assert_eq!(fs::read_to_string(&edited).unwrap(), text);
+    insta::assert_snapshot!(fs::read_to_string(&changed).unwrap());
+}
+
+/// The hashes of the sources of a conversion with downloaded images, which do not change
+/// with the cache directory the images were downloaded into.
+#[test]
+fn converter_hash_downloads() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = "https://old-site.example/images/kueche.jpg";
+    let input = dir.path().join("missions.json");
+    let source = fs::read_to_string(FIXTURE).unwrap();
+    fs::write(&input, source.replace("images/einsaetze/kueche.jpg", url)).unwrap();
+    let converter = |cache: &str| {
+        // Downloaded by an earlier run.
+        let cache_dir = dir.path().join(cache);
+        let downloaded = cache_dir.join(format!("images/{:x}.jpg", Sha256::digest(url)));
+        fs::create_dir_all(downloaded.parent().unwrap()).unwrap();
+        fs::write(downloaded, "kueche.jpg").unwrap();
+        Converter {
+            source: Box::new(JoomlaJson::new(&input)),
+            old_website_dir: dir.path().join("old"),
+            output_dir: dir.path().join("output"),
+            cache_dir,
+            years: vec![2019],
+            existing: Existing::UpdateChanged,
+            converter_hash: true,
+            missing_images: MissingImages::Skip,
+            ..Converter::default()
+        }
+    };
+    converter("cache").convert().unwrap();
+    let edited = dir.path().join("output/content/2019/0001/index.md");
+    let text = format!("{}Nachtrag\n", fs::read_to_string(&edited).unwrap());
+    assert!(text.contains("img-00"));
+    fs::write(&edited, &text).unwrap();
+
+    assert!(converter("other-cache")
+        .convert()
+        .unwrap()
+        .failures
+        .is_empty());
+    assert_eq!(fs::read_to_string(&edited).unwrap(), text);
+}
+
+/// A conversion resumed after an article failed on a missing image, which only writes
+/// that article and ends up with the tree of a conversion in one go.
+#[test]
+fn resume() {
+    let dir = tempfile::tempdir().unwrap();
+    let old_website_dir = old_website(dir.path(), &["kueche.jpg", "vu-1.jpg", "vu-3.jpg"]);
+    let converter = |output_dir: &str, resume| Converter {
+        source: Box::new(JoomlaJson::new(FIXTURE)),
+        old_website_dir: old_website_dir.clone(),
+        output_dir: dir.path().join(output_dir),
+        cache_dir: dir.path().join("cache"),
+        years: vec![2019, 2020],
+        resume,
+        ..Converter::default()
+    };
+    assert_eq!(
+        converter("output", false).convert().unwrap().failures.len(),
+        1
+    );
+    assert!(dir.path().join("output").join(CHECKPOINT_FILE).exists());
+
+    fs::write(
+        old_website_dir.join("images/einsaetze/vu-2.jpg"),
+        "vu-2.jpg",
+    )
+    .unwrap();
+    let conversion = converter("output", true).convert().unwrap();
+    assert!(conversion.failures.is_empty());
+    let statuses: Vec<String> = conversion
+        .report
+        .articles
+        .iter()
+        .map(|article| {
+            format!(
+                "{}-{} {}",
+                article.year,
+                article.number,
+                article.status.unwrap()
+            )
+        })
+        .collect();
+    insta::assert_snapshot!(statuses.join("\n"));
+
+    converter("fresh", false).convert().unwrap();
+    assert_eq!(
+        tree(&dir.path().join("output")),
+        tree(&dir.path().join("fresh"))
+    );
+}
+
+/// A run that fails before it completes an article, here to read the source, leaves the
+/// checkpoint of the run before as it was, to be resumed still.
+#[test]
+fn checkpoint_kept() {
+    let dir = tempfile::tempdir().unwrap();
+    let old_website_dir = old_website(dir.path(), &["kueche.jpg", "vu-1.jpg", "vu-3.jpg"]);
+    let converter = |input: &str| Converter {
+        source: Box::new(JoomlaJson::new(input)),
+        old_website_dir: old_website_dir.clone(),
+        output_dir: dir.path().join("output"),
+        cache_dir: dir.path().join("cache"),
+        years: vec![2019, 2020],
+        ..Converter::default()
+    };
+    assert_eq!(converter(FIXTURE).convert().unwrap().failures.len(), 1);
+    let checkpoint = dir.path().join("output").join(CHECKPOINT_FILE);
+    let written = fs::read_to_string(&checkpoint).unwrap();
+    assert!(!written.is_empty());
+
+    let missing = dir.path().join("missing.json");
+    assert!(converter(missing.to_str().unwrap()).convert().is_err());
+    assert_eq!(fs::read_to_string(&checkpoint).unwrap(), written);
+}
+
+/// A staged year with a failed article is left out of the output, until a later run
+/// completes it.
+#[test]
+fn staging() {
+    let dir = tempfile::tempdir().unwrap();
+    let old_website_dir = old_website(dir.path(), &["kueche.jpg", "vu-1.jpg", "vu-3.jpg"]);
+    let converter = |output_dir: &str, staging| Converter {
+        source: Box::new(JoomlaJson::new(FIXTURE)),
+        old_website_dir: old_website_dir.clone(),
+        output_dir: dir.path().join(output_dir),
+        cache_dir: dir.path().join("cache"),
+        years: vec![2019, 2020],
+        staging,
+        ..Converter::default()
+    };
+    let output_dir = dir.path().join("output");
+    assert_eq!(
+        converter("output", true).convert().unwrap().failures.len(),
+        1
+    );
+    assert!(output_dir.join("content/2019").exists());
+    assert!(!output_dir.join("content/2020").exists());
+    assert!(output_dir.join(STAGING_DIR).join("content/2020").exists());
+
+    fs::write(
+        old_website_dir.join("images/einsaetze/vu-2.jpg"),
+        "vu-2.jpg",
+    )
+    .unwrap();
+    assert!(converter("output", true)
+        .convert()
+        .unwrap()
+        .failures
+        .is_empty());
+    converter("fresh", false).convert().unwrap();
+    assert_eq!(tree(&output_dir), tree(&dir.path().join("fresh")));
+}
+
+/// An export split into files, with an article in two of them, converts like the whole.
+#[test]
+fn merged_inputs() {
+    let dir = tempfile::tempdir().unwrap();
+    let old_website_dir = old_website(dir.path(), &[]);
+    let export: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(FIXTURE).unwrap()).unwrap();
+    let rows = export["data"].as_array().unwrap();
+    let input_dir = dir.path().join("export");
+    fs::create_dir_all(&input_dir).unwrap();
+    for (name, rows) in [
+        ("missions_2019.json", &rows[..4]),
+        ("missions_2020.json", &rows[3..]),
+    ] {
+        let part = serde_json::json!({ "type": "table", "name": "jos_content", "data": rows });
+        fs::write(input_dir.join(name), part.to_string()).unwrap();
+    }
+    fs::write(input_dir.join("README.txt"), "Export of the old website").unwrap();
+
+    let previews = |builder: ConverterBuilder| {
+        builder
+            .old_website_dir(&old_website_dir)
+            .years(vec![2019, 2020])
+            .build()
+            .unwrap()
+            .preview()
+            .unwrap()
+            .into_iter()
+            .map(|(source, rendered)| (source.id, rendered.year, rendered.path))
+            .collect::<Vec<_>>()
+    };
+    let whole = previews(Converter::builder().input(FIXTURE));
+    assert_eq!(previews(Converter::builder().inputs([&input_dir])), whole);
+    assert_eq!(
+        previews(Converter::builder().inputs([
+            input_dir.join("missions_2019.json"),
+            input_dir.join("missions_2020.json"),
+        ])),
+        whole
+    );
+    let sources = Merged::new(vec![
+        Box::new(JoomlaJson::new(input_dir.join("missions_2019.json"))),
+        Box::new(JoomlaJson::new(input_dir.join("missions_2020.json"))),
+    ]);
+    let mut ordinals = Vec::new();
+    sources
+        .stream(&mut |article| ordinals.push((article.ordinal, article.id)))
+        .unwrap();
+    insta::assert_debug_snapshot!(ordinals);
+}
+
+/// The years of the articles in the export when none are given, the newest first, without
+/// the ones of other categories unless they are converted as well and without the trashed
+/// and unpublished ones unless they are drafts.
+#[test]
+fn year_discovery() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut export: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(FIXTURE).unwrap()).unwrap();
+    for row in export["data"].as_array_mut().unwrap() {
+        let created = match row["id"].as_str() {
+            Some("101") => "2017-03-11 11:20:00",
+            Some("103") => "2016-02-01 19:00:00",
+            Some("104") => "2022-11-23 17:45:00",
+            Some("106") => "2021-06-14 15:10:00",
+            Some("107") => "2018-07-01 12:00:00",
+            _ => continue,
+        };
+        row["created"] = created.into();
+    }
+    let input = dir.path().join("missions.json");
+    fs::write(&input, export.to_string()).unwrap();
+    let old_website_dir = old_website(dir.path(), &[]);
+    let builder = || {
+        Converter::builder()
+            .input(&input)
+            .old_website_dir(&old_website_dir)
+            .output_dir(dir.path().join("output"))
+            .cache_dir(dir.path().join("cache"))
+            .missing_images(MissingImages::Skip)
+    };
+    let years = |builder: ConverterBuilder| builder.build().unwrap().years;
+    assert_eq!(years(builder()), [2022, 2020, 2019, 2017]);
+    assert_eq!(
+        years(builder().sections(sections())),
+        [2022, 2020, 2019, 2017, 2016]
+    );
+    assert_eq!(
+        years(builder().drafts(true)),
+        [2022, 2021, 2020, 2019, 2017]
+    );
+    assert_eq!(years(builder().years([2019])), [2019]);
+
+    let converter = builder().build().unwrap();
+    assert!(converter.convert().unwrap().failures.is_empty());
+    for year in [2017, 2019, 2020, 2022] {
+        assert!(dir.path().join(format!("output/content/{}", year)).is_dir());
+    }
+}
+
+/// Only the articles from a cutover date on, with the numbers of their whole year.
+#[test]
+fn date_range() {
+    let dir = tempfile::tempdir().unwrap();
+    let old_website_dir = old_website(dir.path(), &[]);
+    let converter = Converter::builder()
+        .input(FIXTURE)
+        .old_website_dir(&old_website_dir)
+        .output_dir(dir.path().join("output"))
+        .cache_dir(dir.path().join("cache"))
+        .missing_images(MissingImages::Skip)
+        .from(NaiveDate::from_ymd_opt(2019, 3, 1).unwrap())
+        .until(NaiveDate::from_ymd_opt(2020, 6, 30).unwrap())
+        .build()
+        .unwrap();
+    let conversion = converter.convert().unwrap();
+    let statuses: Vec<String> = conversion
+        .report
+        .articles
+        .iter()
+        .map(|article| {
+            format!(
+                "{}-{} {}",
+                article.year,
+                article.number,
+                article.status.unwrap()
+            )
+        })
+        .collect();
+    insta::assert_snapshot!(format!("{:?}\n{}", converter.years, statuses.join("\n")));
+}
+
+/// The translations of a multilingual site, the English one in a category of its own.
+#[test]
+fn multilingual() {
+    let dir = tempfile::tempdir().unwrap();
+    let export = dir.path().join("missions.json");
+    let row = |id, language, association, catid, created: &str, title| {
+        serde_json::json!({
+            "id": id, "title": title, "alias": "", "catid": catid,
+            "created": format!("{} 10:00:00", created),
+            "language": language, "association": association,
+            "introtext": "<p>Text.</p><img src=\"images/einsaetze/brand.jpg\" />",
+        })
+    };
+    let data = [
+        row("201", "de-DE", "a1", "5", "2019-03-01", "Brand: Scheune"),
+        row("202", "en-GB", "a1", "9", "2019-03-02", "Fire: Barn"),
+        row("203", "*", "", "5", "2019-02-01", "Übung"),
+    ];
+    fs::write(&export, serde_json::json!({ "data": data }).to_string()).unwrap();
+    let old_website_dir = old_website(dir.path(), &["brand.jpg"]);
+    let converter = Converter {
+        source: Box::new(JoomlaJson::new(export)),
+        old_website_dir,
+        output_dir: dir.path().join("output"),
+        cache_dir: dir.path().join("cache"),
+        years: vec![2019],
+        multilingual: true,
+        ..Converter::default()
+    };
+    let conversion = converter.convert().unwrap();
+    assert!(conversion.failures.is_empty());
+    insta::assert_snapshot!(tree(&converter.output_dir.join("content")));
+}
+
+/// The report of a conversion with an article left out and missing images skipped.
+#[test]
+fn report() {
+    let dir = tempfile::tempdir().unwrap();
+    let old_website_dir = old_website(dir.path(), &["kueche.jpg"]);
+    let converter = Converter {
+        source: Box::new(JoomlaJson::new(FIXTURE)),
+        old_website_dir,
+        output_dir: dir.path().join("output"),
+        cache_dir: dir.path().join("cache"),
+        years: vec![2019, 2020],
+        missing_images: MissingImages::Skip,
+        select: Some(Box::new(|key| key.year == 2020)),
+        ..Converter::default()
+    };
+    let report = converter.convert().unwrap().report;
+    let dir = dir.path().display().to_string();
+    let json = serde_json::to_string_pretty(&report).unwrap();
+    insta::assert_snapshot!(json.replace(&dir, "<dir>"));
+    insta::assert_snapshot!("report-csv", report.to_csv().replace(&dir, "<dir>"));
+}
+
+/// The errors and warnings of a Hugo build in the report of the articles they are about,
+/// by the paths of their files, from a stand-in for `hugo` that logs like a failed build.
+/// The articles of both sections are in directories named after their slugs.
+#[cfg(unix)]
+#[test]
+fn build_issues() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let converter = Converter {
+        source: Box::new(JoomlaJson::new(FIXTURE)),
+        old_website_dir: old_website(dir.path(), &[]),
+        output_dir: dir.path().join("output"),
+        cache_dir: dir.path().join("cache"),
+        years: vec![2019, 2020],
+        sections: sections(),
+        slug_dirs: true,
+        missing_images: MissingImages::Skip,
+        ..Converter::default()
+    };
+    let mut report = converter.convert().unwrap().report;
+    let content_dir = dir.path().join("output/content");
+    let hugo = dir.path().join("hugo");
+    fs::write(
+        &hugo,
+        format!(
+            concat!(
+                "#!/bin/sh\n",
+                "echo 'ERROR 2024/05/01 12:00:00 \"{0}/2019/2019-03-11-brand-kueche/index.md:7:1\": no image \"img-01\"'\n",
+                "echo 'WARN  2024/05/01 12:00:00 \"{0}/berichte/2019/2019-02-01-jahreshauptversammlung/index.md\": empty'\n",
+                "echo 'WARN  2024/05/01 12:00:00 found no layout file for \"json\"' >&2\n",
+                "exit 1\n",
+            ),
+            content_dir.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&hugo, fs::Permissions::from_mode(0o755)).unwrap();
+    let issues = verify::hugo_build(&hugo, dir.path(), &dir.path().join("public")).unwrap();
+    verify::attach_issues(&mut report, &issues);
+    let dir = dir.path().display().to_string();
+    let build_issues: Vec<(Option<String>, u32, String, Vec<String>)> = report
+        .articles
+        .into_iter()
+        .map(|article| {
+            let issues = article
+                .build_issues
+                .iter()
+                .map(|issue| issue.replace(&dir, "<dir>"))
+                .collect();
+            (article.section, article.year, article.number, issues)
+        })
+        .collect();
+    insta::assert_debug_snapshot!(build_issues);
+}
+
+/// An export with an article without a title among the others, which are converted all
+/// the same. The one without a title fails and is in the report.
+#[test]
+fn invalid_row() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut export: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(FIXTURE).unwrap()).unwrap();
+    let untitled = export["data"]
+        .as_array_mut()
+        .unwrap()
+        .iter_mut()
+        .find(|row| row["catid"] == "5" && row["created"].as_str().unwrap().starts_with("2019"))
+        .unwrap();
+    untitled.as_object_mut().unwrap().remove("title");
+    let input = dir.path().join("missions.json");
+    fs::write(&input, export.to_string()).unwrap();
+    let converter = Converter {
+        source: Box::new(JoomlaJson::new(&input)),
+        old_website_dir: old_website(dir.path(), &[]),
+        output_dir: dir.path().join("output"),
+        cache_dir: dir.path().join("cache"),
+        years: vec![2019, 2020],
+        missing_images: MissingImages::Skip,
+        ..Converter::default()
+    };
+    let conversion = converter.convert().unwrap();
+    let dir = dir.path().display().to_string();
+    let failures: Vec<String> = conversion
+        .failures
+        .iter()
+        .map(|failure| failure.to_string().replace(&dir, "<dir>"))
+        .collect();
+    insta::assert_debug_snapshot!(failures);
+    let json = serde_json::to_string_pretty(&conversion.report).unwrap();
+    insta::assert_snapshot!("invalid_row-report", json.replace(&dir, "<dir>"));
+}
+
+/// The rows with an ID or author that is not a number, which are invalid like the ones
+/// with such a category, and the one with an empty ID, which has none.
+#[test]
+fn invalid_numbers() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut export: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(FIXTURE).unwrap()).unwrap();
+    let rows = export["data"].as_array_mut().unwrap();
+    rows[0]["id"] = "abc".into();
+    rows[1]["created_by"] = "Max Mustermann".into();
+    rows[2]["id"] = "".into();
+    let input = dir.path().join("missions.json");
+    fs::write(&input, export.to_string()).unwrap();
+    let mut ids = Vec::new();
+    let mut invalid = Vec::new();
+    let dir = dir.path().display().to_string();
+    JoomlaJson::new(&input)
+        .stream_all(&mut |article| ids.push(article.id), &mut |error| {
+            invalid.push(error.to_string().replace(&dir, "<dir>"));
+        })
+        .unwrap();
+    assert!(ids.contains(&None));
+    insta::assert_debug_snapshot!(invalid);
+}
+
+/// Every file below `dir` by its path relative to it, the text files with their content.
+fn tree(dir: &Path) -> String {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(current) = dirs.pop() {
+        for entry in fs::read_dir(current).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    let mut tree = String::new();
+    for path in files {
+        let name = path
+            .strip_prefix(dir)
+            .unwrap()
+            .to_string_lossy()
+            .into_owned();
+        tree.push_str(&format!("== {}\n", name));
+        let is_text = ["md", "toml", "yml", "json"]
+            .iter()
+            .any(|extension| path.extension() == Some(extension.as_ref()));
+        if is_text {
+            tree.push_str(&fs::read_to_string(&path).unwrap());
+        }
+    }
+    tree
+}
+
+/// The fixture articles as [`Converter::preview`](ff_website_converter::Converter::preview)
+/// returns them for Hugo.
+fn fixture_previews() -> Vec<(SourceArticle, RenderedArticle)> {
+    let pipeline = Pipeline::builtin();
+    fixture_articles()
+        .into_iter()
+        .map(|(_, index, source)| {
+            let options = RenderOptions {
+                target: &Hugo::default(),
+                pipeline: &pipeline,
+                hooks: &[],
+                section: None,
+                read_more: Some("<!--more-->"),
+                image_format: Default::default(),
+                image_names: None,
+                bundle_thumbnails: false,
+                index,
+                dir: None,
+                old_url: None,
+                multilingual: false,
+                attachments: false,
+                article_paths: None,
+            };
+            let rendered = render::convert_article(&source, &options).unwrap();
+            (source, rendered)
+        })
+        .collect()
+}
+
+#[test]
+fn htaccess() {
+    insta::assert_snapshot!(redirects::htaccess(
+        &fixture_previews(),
+        &Hugo::default(),
+        "https://www.ff-example.de"
+    ));
+}
+
+#[test]
+fn mapping() {
+    let converter = Converter {
+        source: Box::new(JoomlaJson::new(FIXTURE)),
+        years: vec![2019, 2020],
+        old_url: Some("/einsaetze/{catid}-einsaetze/{id}-{alias}.html".to_string()),
+        ..Converter::default()
+    };
+    let mapping = mapping::mapping(&converter.preview().unwrap(), &Hugo::default());
+    insta::assert_snapshot!(serde_json::to_string_pretty(&mapping).unwrap());
+}
+
+/// Joomla links to converted articles become relrefs, those to other articles and
+/// categories are kept and the first reported.
+#[test]
+fn rewrite_links() {
+    let dir = tempfile::tempdir().unwrap();
+    let links = concat!(
+        "\n\n[Unfall](index.php?option=com_content&view=article&id=104:vu&catid=5) ",
+        "[Baum](/einsaetze/5-einsaetze/102-baum.html) ",
+        "[Versammlung](https://www.ff-example.de/index.php?option=com_content&id=103) ",
+        "[Einsätze](index.php?option=com_content&view=category&id=5) ",
+        "[THW](https://www.thw.de/123-hilfe)\n",
+    );
+    let converter = Converter {
+        source: Box::new(JoomlaJson::new(FIXTURE)),
+        output_dir: dir.path().join("output"),
+        cache_dir: dir.path().join("cache"),
+        years: vec![2019, 2020],
+        missing_images: MissingImages::Skip,
+        hooks: vec![Box::new(move |article: &mut Article| {
+            if article.original_id == Some(101) {
+                article.text.push_str(links);
+            }
+        })],
+        rewrite_links: true,
+        ..Converter::default()
+    };
+    let previews = converter.preview().unwrap();
+    let (_, rendered) = previews
+        .iter()
+        .find(|(source, _)| source.id == Some(101))
+        .unwrap();
+    insta::assert_snapshot!(rendered.article.text.split("\n\n").last().unwrap().trim());
+
+    let report = converter.convert().unwrap().report;
+    let warnings: Vec<&str> = report
+        .articles
+        .iter()
+        .flat_map(|article| &article.warnings)
+        .map(String::as_str)
+        .filter(|warning| warning.starts_with("unresolved link"))
+        .collect();
+    assert_eq!(
+        warnings,
+        ["unresolved link https://www.ff-example.de/index.php?option=com_content&id=103"]
+    );
+    assert_eq!(
+        Zola::default().article_link("2019/0001").as_deref(),
+        Some("@/2019/0001/index.md")
+    );
+}
+
+/// The documents an article links to copied into its directory, a missing one reported.
+#[test]
+fn attachments() {
+    let dir = tempfile::tempdir().unwrap();
+    let old_website_dir = dir.path().join("old");
+    for document in [
+        "Einsatzbericht 2019.pdf",
+        "berichte/Einsatzbericht 2019.PDF",
+    ] {
+        let path = old_website_dir.join("images").join(document);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, document).unwrap();
+    }
+    let links = concat!(
+        "\n\n[Bericht](images/Einsatzbericht%202019.pdf) ",
+        "[Noch einmal](/images/Einsatzbericht%202019.pdf#page=2) ",
+        "[Kopie](images/berichte/Einsatzbericht%202019.PDF) ",
+        "[Fehlt](images/alarmplan.docx) ",
+        "[Extern](https://www.thw.de/bericht.pdf) ",
+        "[Seite](index.php?option=com_content&id=104)\n",
+    );
+    let converter = Converter {
+        source: Box::new(JoomlaJson::new(FIXTURE)),
+        old_website_dir,
+        output_dir: dir.path().join("output"),
+        cache_dir: dir.path().join("cache"),
+        years: vec![2019, 2020],
+        missing_images: MissingImages::Skip,
+        hooks: vec![Box::new(move |article: &mut Article| {
+            if article.original_id == Some(101) {
+                article.text.push_str(links);
+            }
+        })],
+        attachments: true,
+        ..Converter::default()
+    };
+    let previews = converter.preview().unwrap();
+    let (_, rendered) = previews
+        .iter()
+        .find(|(source, _)| source.id == Some(101))
+        .unwrap();
+    insta::assert_snapshot!(rendered.article.text.split("\n\n").last().unwrap().trim());
+
+    let report = converter.convert().unwrap().report;
+    let warnings: Vec<&str> = report
+        .articles
+        .iter()
+        .flat_map(|article| &article.warnings)
+        .map(String::as_str)
+        .filter(|warning| warning.starts_with("missing attachment"))
+        .collect();
+    assert_eq!(warnings, ["missing attachment images/alarmplan.docx"]);
+    let files = converter
+        .output_dir
+        .join("content")
+        .join(rendered.year.to_string())
+        .join(rendered.path.parent().unwrap())
+        .join("files");
+    assert_eq!(
+        fs::read_to_string(files.join("einsatzbericht-2019.pdf")).unwrap(),
+        "Einsatzbericht 2019.pdf"
+    );
+    assert_eq!(
+        fs::read_to_string(files.join("einsatzbericht-2019-2.pdf")).unwrap(),
+        "berichte/Einsatzbericht 2019.PDF"
+    );
+}
+
+#[test]
+fn aliases() {
+    let converter = Converter {
+        source: Box::new(JoomlaJson::new(FIXTURE)),
+        years: vec![2019, 2020],
+        old_url: Some("/index.php?option=com_content&view=article&id={id}:{alias}".to_string()),
+        ..Converter::default()
+    };
+    let previews = converter.preview().unwrap();
+    insta::assert_snapshot!(redirects::netlify(&previews, &Hugo::default()));
+
+    let converter = Converter {
+        old_url: Some("/einsaetze/{catid}-einsaetze/{id}-{alias}.html".to_string()),
+        ..converter
+    };
+    let (_, rendered) = converter
+        .preview()
+        .unwrap()
+        .into_iter()
+        .find(|(source, _)| source.alias.is_some())
+        .unwrap();
+    insta::assert_snapshot!("aliases-hugo", rendered.content);
+}
+
+#[test]
+fn calendar() {
+    insta::assert_snapshot!(calendar::ics(
+        &fixture_previews(),
+        &Hugo::default(),
+        "https://www.ff-example.de"
+    ));
+}
+
+#[test]
+fn stats() {
+    insta::assert_json_snapshot!(Stats::new(&fixture_previews()));
+}
+
+/// Every article of the input, whatever its year, category and state.
+#[test]
+fn input_stats() {
+    let converter = Converter {
+        source: Box::new(JoomlaJson::new(FIXTURE)),
+        categories: [(CATID, "Einsätze".to_string())].into(),
+        ..Converter::default()
+    };
+    insta::assert_debug_snapshot!(InputStats::new(&converter).unwrap());
+}
+
+#[test]
+fn section_indexes() {
+    for (name, constructor) in TARGETS {
+        insta::assert_debug_snapshot!(
+            format!("{}-index", name),
+            constructor(&TargetOptions::default()).section_index(&Section::new(CATID), 2019)
+        );
+    }
+}
+
+#[test]
+fn site_configs() {
+    for (name, constructor) in TARGETS {
+        insta::assert_debug_snapshot!(
+            format!("{}-config", name),
+            constructor(&TargetOptions::default())
+                .site_config(&[Section::new(CATID)], &[2019, 2020])
+        );
+    }
+}
+
+#[test]
+fn cms_configs() {
+    for (name, constructor) in TARGETS {
+        insta::assert_snapshot!(
+            format!("{}-cms", name),
+            constructor(&TargetOptions::default())
+                .cms_config(&[Section::new(CATID)], &[2019])
+                .unwrap_or_default()
+        );
+    }
+}
+
+/// Further categories with their own directories, next to the missions.
+fn sections() -> Vec<Section> {
+    vec![
+        Section::new(CATID),
+        Section {
+            catid: 7,
+            dir: Some("berichte".to_string()),
+            title: Some("Berichte".to_string()),
+        },
+    ]
+}
+
+#[test]
+fn section_configs() {
+    let hugo = Hugo::default();
+    insta::assert_snapshot!(
+        "hugo-sections-config",
+        hugo.site_config(&sections(), &[2019]).unwrap()
+    );
+    insta::assert_snapshot!(
+        "hugo-sections-cms",
+        hugo.cms_config(&sections(), &[2019]).unwrap()
+    );
+}
+
+/// The indexes of titles with a colon and quotes, which YAML and TOML need quoted.
+#[test]
+fn index_titles() {
+    let mut options = TargetOptions::default();
+    options.strings.year_index = "{section}: {year}".to_string();
+    let section = Section {
+        catid: 7,
+        dir: Some("berichte".to_string()),
+        title: Some("Berichte: \"Aus dem Verein\"".to_string()),
+    };
+    let targets: [(&str, Box<dyn OutputTarget>); 2] = [
+        ("hugo", Box::new(Hugo::new(options.clone()))),
+        ("zola", Box::new(Zola::new(options))),
+    ];
+    for (name, target) in targets {
+        insta::assert_snapshot!(
+            format!("{}-index-titles", name),
+            [
+                target.section_index(&Section::new(CATID), 2019).unwrap(),
+                target.section_dir_index(&section).unwrap(),
+                target.section_index(&section, 2019).unwrap(),
+            ]
+            .join("")
+        );
+    }
+}
+
+/// The section tree for the Hugo and Zola targets, which keeps an index edited before.
+#[test]
+fn scaffold() {
+    for (name, constructor) in TARGETS {
+        let dir = tempfile::tempdir().unwrap();
+        let edited = dir.path().join("content/2019/_index.md");
+        fs::create_dir_all(edited.parent().unwrap()).unwrap();
+        fs::write(&edited, "---\ntitle: Einsätze im Jahr 2019\n---\n").unwrap();
+        let target = constructor(&TargetOptions::default());
+        let written =
+            output::write_scaffold(dir.path(), &*target, &sections(), &[2019, 2020]).unwrap();
+        assert!(!written.contains(&edited));
+        insta::assert_snapshot!(format!("{}-scaffold", name), tree(dir.path()));
+    }
+}
+
+#[test]
+fn section_article() {
+    let mut source = None;
+    JoomlaJson::new(FIXTURE)
+        .stream(&mut |article| {
+            if article.catid == Some(7) {
+                source = Some(article);
+            }
+        })
+        .unwrap();
+    let rendered = render::convert_article(
+        &source.unwrap(),
+        &RenderOptions {
+            target: &Hugo::default(),
+            pipeline: &Pipeline::builtin(),
+            hooks: &[],
+            section: Some("berichte"),
+            read_more: Some("<!--more-->"),
+            image_format: Default::default(),
+            image_names: None,
+            bundle_thumbnails: false,
+            index: 0,
+            dir: None,
+            old_url: None,
+            multilingual: false,
+            attachments: false,
+            article_paths: None,
+        },
+    )
+    .unwrap();
+    insta::assert_snapshot!("hugo-section-article", rendered.content);
+}